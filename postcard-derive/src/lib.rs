@@ -0,0 +1,25 @@
+//! Derive macros for the [`postcard`](https://docs.rs/postcard) crate.
+//!
+//! These macros are re-exported by `postcard` when its `derive` feature is active, and
+//! should be used from there.
+
+mod max_size;
+mod schema;
+
+/// Derive the `postcard::MaxSize` trait for a struct or enum.
+///
+/// All fields must implement `MaxSize`, and all type parameters are required to
+/// implement `MaxSize` as well.
+#[proc_macro_derive(MaxSize)]
+pub fn derive_max_size(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    max_size::do_derive_max_size(item)
+}
+
+/// Derive the `postcard::Schema` trait for a struct or enum.
+///
+/// All fields must implement `Schema`, and all type parameters are required to
+/// implement `Schema` as well.
+#[proc_macro_derive(Schema)]
+pub fn derive_schema(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    schema::do_derive_schema(item)
+}
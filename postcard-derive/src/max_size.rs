@@ -0,0 +1,89 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Fields, GenericParam,
+    Generics,
+};
+
+pub fn do_derive_max_size(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    let span = input.span();
+    let name = input.ident;
+
+    // Add a bound `T: MaxSize` to every type parameter T.
+    let generics = add_trait_bounds(input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let sum = max_size_sum(&input.data, span).unwrap_or_else(syn::Error::into_compile_error);
+
+    let expanded = quote! {
+        impl #impl_generics ::postcard::MaxSize for #name #ty_generics #where_clause {
+            const POSTCARD_MAX_SIZE: usize = #sum;
+        }
+    };
+
+    expanded.into()
+}
+
+/// Add a bound `T: MaxSize` to every type parameter T.
+fn add_trait_bounds(mut generics: Generics) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            type_param.bounds.push(parse_quote!(::postcard::MaxSize));
+        }
+    }
+    generics
+}
+
+/// Generate a constant expression that sums up the maximum size of the type.
+fn max_size_sum(data: &Data, span: Span) -> Result<TokenStream, syn::Error> {
+    match data {
+        Data::Struct(data) => Ok(sum_fields(&data.fields)),
+        Data::Enum(data) => {
+            let discriminant_size = varint_size(data.variants.len().saturating_sub(1));
+
+            // Generate a sum for each variant
+            let sums = data
+                .variants
+                .iter()
+                .map(|variant| sum_fields(&variant.fields));
+
+            // Find the maximum of the variant sums
+            Ok(quote! {
+                #discriminant_size + {
+                    let mut max = 0;
+                    #(
+                        let size = #sums;
+                        if size > max {
+                            max = size;
+                        }
+                    )*
+                    max
+                }
+            })
+        }
+        Data::Union(_) => Err(syn::Error::new(
+            span,
+            "unions are not supported by `postcard::MaxSize`",
+        )),
+    }
+}
+
+/// Generate a constant expression that sums up the maximum sizes of the fields.
+fn sum_fields(fields: &Fields) -> TokenStream {
+    let recurse = fields.iter().map(|f| {
+        let ty = &f.ty;
+        quote_spanned! { f.span() => <#ty as ::postcard::MaxSize>::POSTCARD_MAX_SIZE }
+    });
+
+    quote! {
+        0 #(+ #recurse)*
+    }
+}
+
+/// The number of bytes used to encode the given value as a varint
+fn varint_size(value: usize) -> usize {
+    let bits = usize::BITS - value.leading_zeros();
+    core::cmp::max(1, bits.div_ceil(7) as usize)
+}
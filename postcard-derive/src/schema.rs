@@ -0,0 +1,116 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Fields, GenericParam,
+    Generics,
+};
+
+pub fn do_derive_schema(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    let span = input.span();
+    let name = input.ident;
+
+    // Add a bound `T: Schema` to every type parameter T.
+    let generics = add_trait_bounds(input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let ty = generate_type(&input.data, span).unwrap_or_else(syn::Error::into_compile_error);
+
+    let name_str = name.to_string();
+    let expanded = quote! {
+        impl #impl_generics ::postcard::Schema for #name #ty_generics #where_clause {
+            const SCHEMA: &'static ::postcard::schema::NamedType = &::postcard::schema::NamedType {
+                name: #name_str,
+                ty: #ty,
+            };
+        }
+    };
+
+    expanded.into()
+}
+
+/// Add a bound `T: Schema` to every type parameter T.
+fn add_trait_bounds(mut generics: Generics) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            type_param.bounds.push(parse_quote!(::postcard::Schema));
+        }
+    }
+    generics
+}
+
+/// Generate the `&'static SdmTy` of the type
+fn generate_type(data: &Data, span: Span) -> Result<TokenStream, syn::Error> {
+    match data {
+        Data::Struct(data) => Ok(generate_struct(&data.fields)),
+        Data::Enum(data) => {
+            let variants = data.variants.iter().map(|v| {
+                let ty = generate_variant(&v.fields);
+                let name = v.ident.to_string();
+                quote! { &::postcard::schema::NamedVariant { name: #name, ty: #ty } }
+            });
+
+            Ok(quote! {
+                &::postcard::schema::SdmTy::Enum(&[
+                    #( #variants ),*
+                ])
+            })
+        }
+        Data::Union(_) => Err(syn::Error::new(
+            span,
+            "unions are not supported by `postcard::Schema`",
+        )),
+    }
+}
+
+fn generate_struct(fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let fields = named_values(fields.named.iter());
+            quote! { &::postcard::schema::SdmTy::Struct(&[ #( #fields ),* ]) }
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = schema_of(&fields.unnamed[0]);
+            quote! { &::postcard::schema::SdmTy::NewtypeStruct(#ty) }
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields.unnamed.iter().map(schema_of);
+            quote! { &::postcard::schema::SdmTy::TupleStruct(&[ #( #fields ),* ]) }
+        }
+        Fields::Unit => quote! { &::postcard::schema::SdmTy::UnitStruct },
+    }
+}
+
+fn generate_variant(fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let fields = named_values(fields.named.iter());
+            quote! { &::postcard::schema::SdmTy::StructVariant(&[ #( #fields ),* ]) }
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = schema_of(&fields.unnamed[0]);
+            quote! { &::postcard::schema::SdmTy::NewtypeVariant(#ty) }
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields.unnamed.iter().map(schema_of);
+            quote! { &::postcard::schema::SdmTy::TupleVariant(&[ #( #fields ),* ]) }
+        }
+        Fields::Unit => quote! { &::postcard::schema::SdmTy::UnitVariant },
+    }
+}
+
+fn named_values<'a>(fields: impl Iterator<Item = &'a syn::Field> + 'a) -> Vec<TokenStream> {
+    fields
+        .map(|f| {
+            let ty = schema_of(f);
+            let name = f.ident.as_ref().unwrap().to_string();
+            quote_spanned! { f.span() => &::postcard::schema::NamedValue { name: #name, ty: #ty } }
+        })
+        .collect()
+}
+
+fn schema_of(field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+    quote_spanned! { field.span() => <#ty as ::postcard::Schema>::SCHEMA }
+}
@@ -0,0 +1,123 @@
+use core::marker::PhantomData;
+
+use heapless::Vec;
+use postcard::{max_size_cobs, to_slice, to_slice_varint, MaxSize};
+use serde::Serialize;
+
+#[allow(dead_code)]
+#[derive(MaxSize)]
+struct Unit;
+
+#[allow(dead_code)]
+#[derive(MaxSize)]
+struct Tuple(u8, u16, u32);
+
+#[allow(dead_code)]
+#[derive(MaxSize, Serialize)]
+struct Named {
+    a: u32,
+    b: Option<bool>,
+    c: [i16; 3],
+    d: Vec<u8, 8>,
+}
+
+#[allow(dead_code)]
+#[derive(MaxSize, Serialize)]
+enum Enum {
+    Unit,
+    Tuple(u8, u64),
+    Struct { a: Named, b: char },
+}
+
+#[allow(dead_code)]
+#[derive(MaxSize)]
+struct Generic<T, U> {
+    t: T,
+    u: [U; 2],
+    p: PhantomData<T>,
+}
+
+#[allow(dead_code)]
+#[derive(MaxSize)]
+enum Empty {}
+
+macro_rules! many_variants {
+    ($name:ident: $($variant:ident)*) => {
+        #[allow(dead_code)]
+        #[derive(MaxSize)]
+        enum $name {
+            $($variant,)*
+        }
+    };
+}
+
+// 128 variants, the largest discriminant is 127
+many_variants!(Small:
+    A0 A1 A2 A3 A4 A5 A6 A7 A8 A9 AA AB AC AD AE AF
+    B0 B1 B2 B3 B4 B5 B6 B7 B8 B9 BA BB BC BD BE BF
+    C0 C1 C2 C3 C4 C5 C6 C7 C8 C9 CA CB CC CD CE CF
+    D0 D1 D2 D3 D4 D5 D6 D7 D8 D9 DA DB DC DD DE DF
+    E0 E1 E2 E3 E4 E5 E6 E7 E8 E9 EA EB EC ED EE EF
+    F0 F1 F2 F3 F4 F5 F6 F7 F8 F9 FA FB FC FD FE FF
+    G0 G1 G2 G3 G4 G5 G6 G7 G8 G9 GA GB GC GD GE GF
+    H0 H1 H2 H3 H4 H5 H6 H7 H8 H9 HA HB HC HD HE HF
+);
+
+// 129 variants, the largest discriminant is 128
+many_variants!(Large:
+    A0 A1 A2 A3 A4 A5 A6 A7 A8 A9 AA AB AC AD AE AF
+    B0 B1 B2 B3 B4 B5 B6 B7 B8 B9 BA BB BC BD BE BF
+    C0 C1 C2 C3 C4 C5 C6 C7 C8 C9 CA CB CC CD CE CF
+    D0 D1 D2 D3 D4 D5 D6 D7 D8 D9 DA DB DC DD DE DF
+    E0 E1 E2 E3 E4 E5 E6 E7 E8 E9 EA EB EC ED EE EF
+    F0 F1 F2 F3 F4 F5 F6 F7 F8 F9 FA FB FC FD FE FF
+    G0 G1 G2 G3 G4 G5 G6 G7 G8 G9 GA GB GC GD GE GF
+    H0 H1 H2 H3 H4 H5 H6 H7 H8 H9 HA HB HC HD HE HF
+    I0
+);
+
+fn full() -> Named {
+    Named {
+        a: u32::MAX,
+        b: Some(true),
+        c: [i16::MIN; 3],
+        d: Vec::from_slice(&[0xFF; 8]).unwrap(),
+    }
+}
+
+#[test]
+fn structs() {
+    assert_eq!(Unit::POSTCARD_MAX_SIZE, 0);
+    assert_eq!(Tuple::POSTCARD_MAX_SIZE, 1 + 3 + 5);
+    assert_eq!(Named::POSTCARD_MAX_SIZE, 5 + 2 + 3 * 3 + 1 + 8);
+    assert_eq!(<Generic<u32, u8>>::POSTCARD_MAX_SIZE, 5 + 2);
+    assert_eq!(<Generic<(), Tuple>>::POSTCARD_MAX_SIZE, 2 * 9);
+
+    let mut buf = [0u8; Named::POSTCARD_MAX_SIZE];
+    assert_eq!(
+        to_slice_varint(&full(), &mut buf).unwrap().len(),
+        Named::POSTCARD_MAX_SIZE
+    );
+    assert!(to_slice(&full(), &mut buf).is_ok());
+}
+
+#[test]
+fn enums() {
+    assert_eq!(Empty::POSTCARD_MAX_SIZE, 1);
+    assert_eq!(Small::POSTCARD_MAX_SIZE, 1);
+    assert_eq!(Large::POSTCARD_MAX_SIZE, 2);
+    assert_eq!(Enum::POSTCARD_MAX_SIZE, 1 + Named::POSTCARD_MAX_SIZE + 5);
+
+    let data = Enum::Struct {
+        a: full(),
+        b: '\u{10FFFF}',
+    };
+    let mut buf = [0u8; Enum::POSTCARD_MAX_SIZE];
+    assert_eq!(
+        to_slice_varint(&data, &mut buf).unwrap().len(),
+        Enum::POSTCARD_MAX_SIZE
+    );
+
+    let mut buf = [0u8; max_size_cobs(Enum::POSTCARD_MAX_SIZE)];
+    assert!(postcard::to_slice_cobs(&data, &mut buf).is_ok());
+}
@@ -0,0 +1,120 @@
+use core::marker::PhantomData;
+
+use postcard::{
+    schema::{NamedType, NamedValue, NamedVariant, SdmTy},
+    Schema,
+};
+
+#[allow(dead_code)]
+#[derive(Schema)]
+struct Unit;
+
+#[allow(dead_code)]
+#[derive(Schema)]
+struct Newtype(u32);
+
+#[allow(dead_code)]
+#[derive(Schema)]
+struct Tuple(u8, i64);
+
+#[allow(dead_code)]
+#[derive(Schema)]
+struct Named<'a> {
+    a: &'a str,
+    b: Option<[u16; 2]>,
+    c: &'a [u8],
+}
+
+#[allow(dead_code)]
+#[derive(Schema)]
+enum Enum<T> {
+    Unit,
+    Newtype(T),
+    Tuple(u8, bool),
+    Struct { a: char, b: PhantomData<T> },
+}
+
+#[test]
+fn structs() {
+    assert_eq!(
+        Unit::SCHEMA,
+        &NamedType {
+            name: "Unit",
+            ty: &SdmTy::UnitStruct,
+        }
+    );
+    assert_eq!(Newtype::SCHEMA.ty, &SdmTy::NewtypeStruct(u32::SCHEMA));
+    assert_eq!(
+        Tuple::SCHEMA.ty,
+        &SdmTy::TupleStruct(&[u8::SCHEMA, i64::SCHEMA])
+    );
+    assert_eq!(
+        Named::SCHEMA,
+        &NamedType {
+            name: "Named",
+            ty: &SdmTy::Struct(&[
+                &NamedValue {
+                    name: "a",
+                    ty: &NamedType {
+                        name: "str",
+                        ty: &SdmTy::String,
+                    },
+                },
+                &NamedValue {
+                    name: "b",
+                    ty: &NamedType {
+                        name: "Option<T>",
+                        ty: &SdmTy::Option(&NamedType {
+                            name: "[T; N]",
+                            ty: &SdmTy::Tuple(&[u16::SCHEMA, u16::SCHEMA]),
+                        }),
+                    },
+                },
+                &NamedValue {
+                    name: "c",
+                    ty: &NamedType {
+                        name: "[T]",
+                        ty: &SdmTy::Seq(u8::SCHEMA),
+                    },
+                },
+            ]),
+        }
+    );
+}
+
+#[test]
+fn enums() {
+    assert_eq!(
+        <Enum<Newtype>>::SCHEMA,
+        &NamedType {
+            name: "Enum",
+            ty: &SdmTy::Enum(&[
+                &NamedVariant {
+                    name: "Unit",
+                    ty: &SdmTy::UnitVariant,
+                },
+                &NamedVariant {
+                    name: "Newtype",
+                    ty: &SdmTy::NewtypeVariant(Newtype::SCHEMA),
+                },
+                &NamedVariant {
+                    name: "Tuple",
+                    ty: &SdmTy::TupleVariant(&[u8::SCHEMA, bool::SCHEMA]),
+                },
+                &NamedVariant {
+                    name: "Struct",
+                    ty: &SdmTy::StructVariant(&[
+                        &NamedValue {
+                            name: "a",
+                            ty: char::SCHEMA,
+                        },
+                        &NamedValue {
+                            name: "b",
+                            ty: <PhantomData<Newtype>>::SCHEMA,
+                        },
+                    ]),
+                },
+            ]),
+        }
+    );
+}
@@ -0,0 +1,380 @@
+//! # Decoders - Framing schemes for accumulators
+//!
+//! The [`Accumulator`] collects chunked input into frames, and deserializes each complete
+//! frame. The framing scheme itself is provided by a decoder, which implements the
+//! [`FrameDecoder`] trait. The accumulator takes care of buffering the decoded bytes, and of
+//! discarding frames that are too large or invalid, so decoders only need to handle the
+//! encoding of the frames.
+//!
+//! Decoders for the framing schemes of the serialization flavors are provided in this module.
+//! Custom decoders may be defined by users of the `postcard` crate as well.
+//!
+//! ## Examples
+//!
+//! Decoders are usually used through the type aliases for the accumulators, such as
+//! [`CobsAccumulator`], but may be combined as needed. Here, a CRC-32 is checked for each
+//! COBS frame:
+//!
+//! ```rust
+//! # #[cfg(feature = "use-crc")] {
+//! use crc::{Crc, CRC_32_ISCSI};
+//! use postcard::{
+//!     decoders,
+//!     flavors::{self, Cobs, Slice},
+//!     serialize_with_flavor, Accumulator, FeedResult,
+//! };
+//!
+//! const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+//!
+//! let mut buf = [0u8; 32];
+//! let frame = serialize_with_flavor::<u16, flavors::Crc<Cobs<Slice>, _>, &mut [u8]>(
+//!     &0x1234,
+//!     flavors::Crc::new(Cobs::try_new(Slice::new(&mut buf)).unwrap(), CRC.digest()),
+//! )
+//! .unwrap();
+//!
+//! let mut acc: Accumulator<_, 32> =
+//!     Accumulator::with_decoder(decoders::Crc::new(decoders::Cobs::new(), CRC.digest()));
+//! match acc.feed::<u16>(frame) {
+//!     FeedResult::Success { data, remaining } => {
+//!         assert_eq!(data, 0x1234);
+//!         assert!(remaining.is_empty());
+//!     }
+//!     _ => panic!(),
+//! }
+//! # }
+//! ```
+//!
+//! [`Accumulator`]: ../struct.Accumulator.html
+//! [`CobsAccumulator`]: ../type.CobsAccumulator.html
+
+use crate::error::Result;
+use crate::hdlc;
+use crate::ser::flavors::PrefixFormat;
+use crate::slip;
+use crate::varint::varint_max;
+use core::ops::Range;
+
+#[cfg(feature = "use-crc")]
+pub use crc_decoder::*;
+
+/// A framing scheme, which is decoded one byte at a time by an [`Accumulator`].
+///
+/// [`Accumulator`]: ../struct.Accumulator.html
+pub trait FrameDecoder {
+    /// Decode the next byte of input.
+    fn decode(&mut self, byte: u8) -> Decoded;
+
+    /// Check a complete frame, made up of all bytes decoded since the end of the previous
+    /// frame, and return the range of it which holds the message.
+    ///
+    /// By default, the whole frame is the message.
+    fn finish(&mut self, frame: &mut [u8]) -> Result<Range<usize>> {
+        Ok(0..frame.len())
+    }
+}
+
+/// The result of decoding a single byte of input with a [`FrameDecoder`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub enum Decoded {
+    /// The byte is part of the framing, such as an escape byte or a length prefix.
+    Nothing,
+
+    /// A byte of the frame.
+    Byte(u8),
+
+    /// The last byte of the frame, which is complete now.
+    Last(u8),
+
+    /// The end of the frame, which is complete now.
+    End,
+
+    /// The end of the frame, which is known to be invalid, e.g. due to a bad escape sequence.
+    Invalid,
+}
+
+/// Decoder for [Consistent Overhead Byte Stuffing] frames, as produced by the
+/// [`Cobs`](../flavors/struct.Cobs.html) flavor.
+///
+/// Empty frames, such as a zero byte sent to flush the line, are ignored.
+///
+/// [Consistent Overhead Byte Stuffing]: https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct Cobs {
+    started: bool,
+    // Data bytes left in the current block
+    remaining: u8,
+    // Whether the current block is followed by a zero, if it is not the last block
+    zero: bool,
+}
+
+impl Cobs {
+    /// Create a new COBS decoder
+    pub const fn new() -> Self {
+        Cobs {
+            started: false,
+            remaining: 0,
+            zero: false,
+        }
+    }
+}
+
+impl FrameDecoder for Cobs {
+    fn decode(&mut self, byte: u8) -> Decoded {
+        if byte == 0 {
+            let state = core::mem::take(self);
+            return match (state.started, state.remaining) {
+                (false, _) => Decoded::Nothing,
+                (true, 0) => Decoded::End,
+                (true, _) => Decoded::Invalid,
+            };
+        }
+
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            return Decoded::Byte(byte);
+        }
+
+        // Start of the next block, which implies the zero at the end of the previous one
+        let zero = self.started && self.zero;
+        self.started = true;
+        self.remaining = byte - 1;
+        self.zero = byte != 0xFF;
+        if zero {
+            Decoded::Byte(0)
+        } else {
+            Decoded::Nothing
+        }
+    }
+}
+
+/// Decoder for SLIP (RFC 1055) frames, as produced by the [`Slip`](../flavors/struct.Slip.html)
+/// flavor.
+///
+/// Empty frames, such as those caused by a leading `END` byte, are ignored.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct Slip {
+    state: EscapeState,
+}
+
+impl Slip {
+    /// Create a new SLIP decoder
+    pub const fn new() -> Self {
+        Slip {
+            state: EscapeState::new(),
+        }
+    }
+}
+
+impl FrameDecoder for Slip {
+    fn decode(&mut self, byte: u8) -> Decoded {
+        self.state
+            .decode(byte, slip::END, slip::ESC, slip::unescape)
+    }
+}
+
+/// Decoder for HDLC-like (RFC 1662) frames, as produced by the
+/// [`Hdlc`](../flavors/struct.Hdlc.html) flavor.
+///
+/// The frame check sequence of each frame is verified and removed. Empty frames, such as
+/// those between a closing and an opening flag, are ignored.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct Hdlc {
+    state: EscapeState,
+}
+
+impl Hdlc {
+    /// Create a new HDLC decoder
+    pub const fn new() -> Self {
+        Hdlc {
+            state: EscapeState::new(),
+        }
+    }
+}
+
+impl FrameDecoder for Hdlc {
+    fn decode(&mut self, byte: u8) -> Decoded {
+        self.state
+            .decode(byte, hdlc::FLAG, hdlc::ESC, hdlc::unescape)
+    }
+
+    fn finish(&mut self, frame: &mut [u8]) -> Result<Range<usize>> {
+        hdlc::check_fcs(frame).map(|len| 0..len)
+    }
+}
+
+/// The decoding state of the byte stuffing [`Slip`] and [`Hdlc`] decoders
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+struct EscapeState {
+    started: bool,
+    // The previous byte was an escape byte
+    escaped: bool,
+    // The frame contained a bad escape sequence, discarding until the end of the frame
+    invalid: bool,
+}
+
+impl EscapeState {
+    const fn new() -> Self {
+        EscapeState {
+            started: false,
+            escaped: false,
+            invalid: false,
+        }
+    }
+
+    fn decode(&mut self, byte: u8, end: u8, esc: u8, unescape: fn(u8) -> Result<u8>) -> Decoded {
+        if byte == end {
+            let state = core::mem::take(self);
+            return if state.escaped || state.invalid {
+                Decoded::Invalid
+            } else if state.started {
+                Decoded::End
+            } else {
+                Decoded::Nothing
+            };
+        }
+
+        self.started = true;
+        if self.invalid {
+            Decoded::Nothing
+        } else if self.escaped {
+            self.escaped = false;
+            match unescape(byte) {
+                Ok(byte) => Decoded::Byte(byte),
+                Err(_) => {
+                    self.invalid = true;
+                    Decoded::Nothing
+                }
+            }
+        } else if byte == esc {
+            self.escaped = true;
+            Decoded::Nothing
+        } else {
+            Decoded::Byte(byte)
+        }
+    }
+}
+
+/// Decoder for length prefixed frames, as produced by the
+/// [`LengthPrefix`](../flavors/struct.LengthPrefix.html) flavor.
+///
+/// A bad length prefix is reported as an invalid frame, after which decoding continues with
+/// the next byte.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct LengthPrefix {
+    format: PrefixFormat,
+    prefix: [u8; varint_max::<u32>()],
+    prefix_len: usize,
+    // Bytes left in the current frame, after the length prefix
+    remaining: usize,
+}
+
+impl LengthPrefix {
+    /// Create a new decoder, for frames with the given length prefix format
+    pub const fn new(format: PrefixFormat) -> Self {
+        LengthPrefix {
+            format,
+            prefix: [0; varint_max::<u32>()],
+            prefix_len: 0,
+            remaining: 0,
+        }
+    }
+}
+
+impl FrameDecoder for LengthPrefix {
+    fn decode(&mut self, byte: u8) -> Decoded {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            return if self.remaining == 0 {
+                Decoded::Last(byte)
+            } else {
+                Decoded::Byte(byte)
+            };
+        }
+
+        self.prefix[self.prefix_len] = byte;
+        self.prefix_len += 1;
+        match self.format.decode(&self.prefix[..self.prefix_len]) {
+            Ok(None) => Decoded::Nothing,
+            Ok(Some((_, len))) => {
+                self.prefix_len = 0;
+                self.remaining = len;
+                if len == 0 {
+                    Decoded::End
+                } else {
+                    Decoded::Nothing
+                }
+            }
+            Err(_) => {
+                self.prefix_len = 0;
+                Decoded::Invalid
+            }
+        }
+    }
+}
+
+#[cfg(feature = "use-crc")]
+mod crc_decoder {
+    use super::{Decoded, FrameDecoder};
+    use crate::checksum::CrcDigest;
+    use crate::error::{Error, Result};
+    use core::ops::Range;
+
+    /// The `Crc` decoder verifies the [Cyclic Redundancy Check] appended to each frame of an
+    /// inner decoder, as produced by the [`Crc`](../flavors/struct.Crc.html) flavor, and
+    /// removes it from the message.
+    ///
+    /// Frames with a bad checksum are reported as a [`FeedResult::DeserError`].
+    ///
+    /// This type is only available when the (non-default) `use-crc` feature is active.
+    ///
+    /// [Cyclic Redundancy Check]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+    /// [`FeedResult::DeserError`]: ../enum.FeedResult.html#variant.DeserError
+    #[derive(Clone)]
+    pub struct Crc<D, C> {
+        decoder: D,
+        digest: C,
+    }
+
+    impl<D, C> Crc<D, C> {
+        /// Create a new Crc decoder, checking the frames of `decoder` using the given digest.
+        /// A copy of the digest is used for each frame.
+        pub const fn new(decoder: D, digest: C) -> Self {
+            Self { decoder, digest }
+        }
+    }
+
+    impl<D, C> FrameDecoder for Crc<D, C>
+    where
+        D: FrameDecoder,
+        C: CrcDigest + Clone,
+    {
+        #[inline]
+        fn decode(&mut self, byte: u8) -> Decoded {
+            self.decoder.decode(byte)
+        }
+
+        fn finish(&mut self, frame: &mut [u8]) -> Result<Range<usize>> {
+            let range = self.decoder.finish(frame)?;
+            let end = range
+                .end
+                .checked_sub(C::SIZE)
+                .filter(|end| *end >= range.start)
+                .ok_or(Error::DeserializeUnexpectedEnd)?;
+
+            let mut digest = self.digest.clone();
+            digest.update(&frame[range.start..end]);
+            let expected = digest.finalize().to_le_bytes();
+            if frame[end..range.end] != expected[..C::SIZE] {
+                return Err(Error::DeserializeBadCrc);
+            }
+            Ok(range.start..end)
+        }
+    }
+}
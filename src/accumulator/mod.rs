@@ -0,0 +1,997 @@
+//! Accumulators used to collect chunked, framed data and deserialize it.
+
+pub mod decoders;
+
+use crate::error::{Error, Result};
+use decoders::{Decoded, FrameDecoder};
+use serde::Deserialize;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// An accumulator used to collect chunked, framed data and deserialize it.
+///
+/// The framing scheme is provided by a [`FrameDecoder`], see the [`decoders`] module. Data is
+/// decoded as it is fed, and stored in an internal buffer of `N` bytes. Frames that do not fit
+/// into the internal buffer are discarded up to their end, and reported as
+/// [`FeedResult::OverFull`]. Invalid frames are reported as [`FeedResult::DeserError`]. In both
+/// cases, the accumulator continues with the next frame.
+///
+/// The received frames are reported to a [`FrameMonitor`] of type `M`, such as the
+/// [`StatsMonitor`]. By default, no monitor is used.
+///
+/// Type aliases are provided for the framing schemes of the serialization flavors, such as the
+/// [`CobsAccumulator`].
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct Accumulator<D, const N: usize, M = ()> {
+    buf: [u8; N],
+    framer: Framer<D, M>,
+}
+
+/// The result of feeding the accumulator.
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub enum FeedResult<'a, T> {
+    /// Consumed all data, still pending.
+    Consumed,
+
+    /// Buffer was filled. Contains remaining section of input, if any.
+    OverFull(&'a [u8]),
+
+    /// Reached end of chunk, but deserialization failed. Contains remaining section of input, if.
+    /// any
+    DeserError(&'a [u8]),
+
+    /// Deserialization complete. Contains deserialized data and remaining section of input, if any.
+    Success {
+        /// Deserialize data.
+        data: T,
+
+        /// Remaining data left in the buffer after deserializing.
+        remaining: &'a [u8],
+    },
+}
+
+impl<D, const N: usize> Accumulator<D, N> {
+    /// Create a new accumulator, using the given decoder.
+    pub const fn with_decoder(decoder: D) -> Self {
+        Self::with_monitor(decoder, ())
+    }
+}
+
+impl<D, const N: usize, M> Accumulator<D, N, M> {
+    /// Create a new accumulator, using the given decoder, which reports the received frames
+    /// to `monitor`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use postcard::{decoders, CobsAccumulator, FeedResult, StatsMonitor};
+    ///
+    /// let mut acc: CobsAccumulator<4, StatsMonitor> =
+    ///     CobsAccumulator::with_monitor(decoders::Cobs::new(), StatsMonitor::new());
+    /// // A frame that is too long, followed by an empty message
+    /// let input = &[0x06, 0x01, 0x02, 0x03, 0x04, 0x05, 0x00, 0x01, 0x00];
+    ///
+    /// let remaining = match acc.feed::<()>(input) {
+    ///     FeedResult::OverFull(remaining) => remaining,
+    ///     _ => panic!(),
+    /// };
+    /// assert!(matches!(acc.feed::<()>(remaining), FeedResult::Success { .. }));
+    ///
+    /// let stats = acc.monitor().stats();
+    /// assert_eq!((stats.frames_ok, stats.overflows), (1, 1));
+    /// assert_eq!(stats.bytes_discarded, 7);
+    /// ```
+    pub const fn with_monitor(decoder: D, monitor: M) -> Self {
+        Accumulator {
+            buf: [0; N],
+            framer: Framer::new(decoder, monitor),
+        }
+    }
+
+    /// Get the monitor of this accumulator.
+    pub fn monitor(&self) -> &M {
+        &self.framer.monitor
+    }
+
+    /// Get the monitor of this accumulator mutably, for example to reset it.
+    pub fn monitor_mut(&mut self) -> &mut M {
+        &mut self.framer.monitor
+    }
+}
+
+impl<D: FrameDecoder + Default, const N: usize, M: Default> Default for Accumulator<D, N, M> {
+    fn default() -> Self {
+        Self::with_monitor(D::default(), M::default())
+    }
+}
+
+impl<D: FrameDecoder, const N: usize, M: FrameMonitor> Accumulator<D, N, M> {
+    /// Appends data to the internal buffer and attempts to deserialize the accumulated data into
+    /// `T`.
+    pub fn feed<'a, T>(&mut self, input: &'a [u8]) -> FeedResult<'a, T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.feed_ref(input)
+    }
+
+    /// Appends data to the internal buffer and attempts to deserialize the accumulated data into
+    /// `T`.
+    ///
+    /// This differs from [`feed`](Accumulator::feed), as `T` may borrow from the internal
+    /// buffer of the accumulator, for example to deserialize a `&str` or `&[u8]` without
+    /// copying it. The accumulator can not be fed again while `T` is in use.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use postcard::{to_slice_cobs, CobsAccumulator, FeedResult};
+    ///
+    /// let mut buf = [0u8; 32];
+    /// let frame = to_slice_cobs("Hello", &mut buf).unwrap();
+    ///
+    /// let mut acc: CobsAccumulator<32> = CobsAccumulator::new();
+    /// match acc.feed_ref::<&str>(frame) {
+    ///     FeedResult::Success { data, remaining } => {
+    ///         assert_eq!(data, "Hello");
+    ///         assert!(remaining.is_empty());
+    ///     }
+    ///     _ => panic!(),
+    /// }
+    /// ```
+    pub fn feed_ref<'de, 'a, T>(&'de mut self, input: &'a [u8]) -> FeedResult<'a, T>
+    where
+        T: Deserialize<'de>,
+    {
+        let frame = self.decode(input);
+        self.framer.finish(frame, &mut self.buf)
+    }
+
+    /// Reads from an [`embedded_io_async::BufRead`] implementation, such as an Embassy
+    /// buffered UART driver, until a frame is complete, and deserializes it into `T`. The
+    /// reader is not read beyond the end of the frame, so this can be called in a loop to
+    /// receive one message after another. Requires the `embedded-io-async` feature.
+    ///
+    /// Frames which are too large or invalid are returned as an error, and the next call
+    /// continues with the following frame. If the reader reaches its end,
+    /// [`Error::DeserializeUnexpectedEnd`] is returned, and if it returns an error,
+    /// [`Error::DeserializeIoError`]. A partial frame is kept in both cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use embassy_futures::block_on;
+    /// use postcard::{CobsAccumulator, Error};
+    ///
+    /// let mut input: &[u8] = &[
+    ///     0x03, 0x01, 0x02, 0x00, // [1, 2]
+    ///     0x0A, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x00, // Too large
+    ///     0x03, 0x03, 0x04, 0x00, // [3, 4]
+    ///     0x03, 0x05, // Partial frame
+    /// ];
+    /// let mut acc: CobsAccumulator<8> = CobsAccumulator::new();
+    ///
+    /// block_on(async {
+    ///     assert_eq!(acc.read_frame::<[u8; 2], _>(&mut input).await, Ok([1, 2]));
+    ///     assert_eq!(
+    ///         acc.read_frame::<[u8; 2], _>(&mut input).await,
+    ///         Err(Error::DeserializeBufferFull)
+    ///     );
+    ///     assert_eq!(acc.read_frame::<[u8; 2], _>(&mut input).await, Ok([3, 4]));
+    ///     assert_eq!(
+    ///         acc.read_frame::<[u8; 2], _>(&mut input).await,
+    ///         Err(Error::DeserializeUnexpectedEnd)
+    ///     );
+    /// });
+    /// ```
+    ///
+    /// [`Error::DeserializeUnexpectedEnd`]: enum.Error.html#variant.DeserializeUnexpectedEnd
+    /// [`Error::DeserializeIoError`]: enum.Error.html#variant.DeserializeIoError
+    #[cfg(feature = "embedded-io-async")]
+    pub async fn read_frame<T, R>(&mut self, reader: &mut R) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: embedded_io_async::BufRead,
+    {
+        loop {
+            let input = reader
+                .fill_buf()
+                .await
+                .map_err(|_| Error::DeserializeIoError)?;
+            let (res, used) = self.read_step::<T>(input);
+            reader.consume(used);
+            if let Some(res) = res {
+                return res;
+            }
+        }
+    }
+
+    /// Reads from a [`futures_io::AsyncBufRead`] implementation until a frame is complete, and
+    /// deserializes it into `T`. Requires the `futures-io` feature.
+    ///
+    /// This is the counterpart to [`read_frame`](Accumulator::read_frame), see its
+    /// documentation for the errors returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use postcard::{CobsAccumulator, Error};
+    ///
+    /// let mut input: &[u8] = &[0x03, 0x01, 0x02, 0x00, 0x03, 0x03, 0x04, 0x00];
+    /// let mut acc: CobsAccumulator<8> = CobsAccumulator::new();
+    ///
+    /// block_on(async {
+    ///     assert_eq!(acc.read_futures_frame::<[u8; 2], _>(&mut input).await, Ok([1, 2]));
+    ///     assert_eq!(acc.read_futures_frame::<[u8; 2], _>(&mut input).await, Ok([3, 4]));
+    ///     assert_eq!(
+    ///         acc.read_futures_frame::<[u8; 2], _>(&mut input).await,
+    ///         Err(Error::DeserializeUnexpectedEnd)
+    ///     );
+    /// });
+    /// ```
+    #[cfg(feature = "futures-io")]
+    pub async fn read_futures_frame<T, R>(&mut self, reader: &mut R) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: futures_io::AsyncBufRead + Unpin,
+    {
+        use core::pin::Pin;
+
+        loop {
+            let (res, used) = core::future::poll_fn(|cx| {
+                Pin::new(&mut *reader)
+                    .poll_fill_buf(cx)
+                    .map(|input| match input {
+                        Ok(input) => self.read_step::<T>(input),
+                        Err(_) => (Some(Err(Error::DeserializeIoError)), 0),
+                    })
+            })
+            .await;
+            Pin::new(&mut *reader).consume(used);
+            if let Some(res) = res {
+                return res;
+            }
+        }
+    }
+
+    /// Feed the input returned by a reader, for [`read_frame`](Accumulator::read_frame).
+    /// Returns the result once a frame is complete, and the number of input bytes to consume.
+    #[cfg(any(feature = "embedded-io-async", feature = "futures-io"))]
+    fn read_step<T>(&mut self, input: &[u8]) -> (Option<Result<T>>, usize)
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if input.is_empty() {
+            return (Some(Err(Error::DeserializeUnexpectedEnd)), 0);
+        }
+        let len = input.len();
+        match self.feed_frame::<T>(input) {
+            Some((res, remaining)) => (Some(res.map_err(Error::from)), len - remaining.len()),
+            None => (None, len),
+        }
+    }
+
+    /// Like [`feed_ref`](Accumulator::feed_ref), but returns the reason a frame was
+    /// discarded. Returns `None` if all input was consumed.
+    #[cfg(any(feature = "use-std", feature = "embedded-io-async"))]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn feed_frame<'de, 'a, T>(
+        &'de mut self,
+        input: &'a [u8],
+    ) -> Option<(core::result::Result<T, FrameError>, &'a [u8])>
+    where
+        T: Deserialize<'de>,
+    {
+        let frame = self.decode(input);
+        self.framer.complete(frame, &mut self.buf)
+    }
+
+    fn decode<'a>(&mut self, input: &'a [u8]) -> Frame<'a> {
+        let buf = &mut self.buf;
+        self.framer
+            .decode(input, |idx, byte| match buf.get_mut(idx) {
+                Some(slot) => {
+                    *slot = byte;
+                    true
+                }
+                None => false,
+            })
+    }
+}
+
+/// An accumulator used to collect chunked COBS data and deserialize it.
+///
+/// This is often useful when you receive "parts" of the message at a time, for example when draining
+/// a serial port buffer that may not contain an entire uninterrupted message.
+///
+/// This is an [`Accumulator`] using the [`Cobs`](decoders::Cobs) decoder. Data is decoded as it
+/// is fed, so the internal buffer of `N` bytes only needs to hold the decoded message. Frames
+/// that do not fit into the internal buffer are discarded up to the next zero byte, and reported
+/// as [`FeedResult::OverFull`].
+///
+/// # Examples
+///
+/// Deserialize a struct by reading chunks from a [`Read`]er. With the `use-std` feature, the
+/// [`CobsStream`](struct.CobsStream.html) iterator may be used for this instead.
+///
+/// ```rust
+/// use postcard::{CobsAccumulator, FeedResult};
+/// use serde::Deserialize;
+/// use std::io::Read;
+///
+/// # let mut input_buf = [0u8; 256];
+/// # #[derive(serde::Serialize, Deserialize, Debug, PartialEq, Eq)]
+/// # struct MyData {
+/// #     a: u32,
+/// #     b: bool,
+/// #     c: [u8; 16],
+/// # }
+/// let input = /* Anything that implements the `Read` trait */
+/// # postcard::to_slice_cobs(&MyData {
+/// #     a: 0xabcdef00,
+/// #     b: true,
+/// #     c: [0xab; 16],
+/// # }, &mut input_buf).unwrap();
+/// # let mut input = &input[..];
+///
+/// let mut raw_buf = [0u8; 32];
+/// let mut cobs_buf: CobsAccumulator<256> = CobsAccumulator::new();
+///
+/// while let Ok(ct) = input.read(&mut raw_buf) {
+///     // Finished reading input
+///     if ct == 0 {
+///         break;
+///     }
+///
+///     let buf = &raw_buf[..ct];
+///     let mut window = &buf[..];
+///
+///     'cobs: while !window.is_empty() {
+///         window = match cobs_buf.feed::<MyData>(&window) {
+///             FeedResult::Consumed => break 'cobs,
+///             FeedResult::OverFull(new_wind) => new_wind,
+///             FeedResult::DeserError(new_wind) => new_wind,
+///             FeedResult::Success { data, remaining } => {
+///                 // Do something with `data: MyData` here.
+///
+///                 dbg!(data);
+///
+///                 remaining
+///             }
+///         };
+///     }
+/// }
+/// ```
+///
+/// [`Read`]: std::io::Read
+pub type CobsAccumulator<const N: usize, M = ()> = Accumulator<decoders::Cobs, N, M>;
+
+impl<const N: usize> CobsAccumulator<N> {
+    /// Create a new accumulator.
+    pub const fn new() -> Self {
+        Self::with_decoder(decoders::Cobs::new())
+    }
+}
+
+/// An accumulator used to collect chunked, length prefixed data and deserialize it.
+///
+/// This is the counterpart to the [`LengthPrefix`] flavor, and is useful for splitting a
+/// reliable byte stream, such as a TCP connection, into messages. It is an [`Accumulator`] using
+/// the [`LengthPrefix`](decoders::LengthPrefix) decoder. Frames that do not fit into the
+/// internal buffer of `N` bytes (not including the length prefix) are skipped, and reported as
+/// [`FeedResult::OverFull`].
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{flavors::PrefixFormat, to_slice_length_prefix, FeedResult, LengthPrefixAccumulator};
+///
+/// let mut buf = [0u8; 32];
+/// let frame = to_slice_length_prefix(&(1u8, 0x0203u16), &mut buf, PrefixFormat::U16).unwrap();
+///
+/// let mut acc: LengthPrefixAccumulator<32> = LengthPrefixAccumulator::new(PrefixFormat::U16);
+/// let (first, second) = frame.split_at(2);
+///
+/// assert!(matches!(acc.feed::<(u8, u16)>(first), FeedResult::Consumed));
+/// match acc.feed::<(u8, u16)>(second) {
+///     FeedResult::Success { data, remaining } => {
+///         assert_eq!(data, (1, 0x0203));
+///         assert!(remaining.is_empty());
+///     }
+///     _ => panic!(),
+/// }
+/// ```
+///
+/// [`LengthPrefix`]: flavors/struct.LengthPrefix.html
+pub type LengthPrefixAccumulator<const N: usize, M = ()> =
+    Accumulator<decoders::LengthPrefix, N, M>;
+
+impl<const N: usize> LengthPrefixAccumulator<N> {
+    /// Create a new accumulator, for frames with the given length prefix format.
+    pub const fn new(format: crate::flavors::PrefixFormat) -> Self {
+        Self::with_decoder(decoders::LengthPrefix::new(format))
+    }
+}
+
+/// An accumulator used to collect chunked SLIP data and deserialize it.
+///
+/// This is the counterpart to the [`Slip`] flavor, and an [`Accumulator`] using the
+/// [`Slip`](decoders::Slip) decoder. Data is unescaped as it is fed, so the internal buffer of
+/// `N` bytes only needs to hold the decoded message. Empty frames, such as those caused by a
+/// leading `END` byte, are ignored. Frames that do not fit into the internal buffer are
+/// discarded up to the next `END` byte, and reported as [`FeedResult::OverFull`].
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{to_slice_slip, FeedResult, SlipAccumulator};
+///
+/// let mut buf = [0u8; 32];
+/// let frame = to_slice_slip(&(0xC0u8, 0xDBu8), &mut buf).unwrap();
+///
+/// let mut acc: SlipAccumulator<32> = SlipAccumulator::new();
+/// let (first, second) = frame.split_at(2);
+///
+/// assert!(matches!(acc.feed::<(u8, u8)>(first), FeedResult::Consumed));
+/// match acc.feed::<(u8, u8)>(second) {
+///     FeedResult::Success { data, remaining } => {
+///         assert_eq!(data, (0xC0, 0xDB));
+///         assert!(remaining.is_empty());
+///     }
+///     _ => panic!(),
+/// }
+/// ```
+///
+/// [`Slip`]: flavors/struct.Slip.html
+pub type SlipAccumulator<const N: usize, M = ()> = Accumulator<decoders::Slip, N, M>;
+
+impl<const N: usize> SlipAccumulator<N> {
+    /// Create a new accumulator.
+    pub const fn new() -> Self {
+        Self::with_decoder(decoders::Slip::new())
+    }
+}
+
+/// An accumulator used to collect chunked HDLC framed data and deserialize it.
+///
+/// This is the counterpart to the [`Hdlc`] flavor, and an [`Accumulator`] using the
+/// [`Hdlc`](decoders::Hdlc) decoder. It works like the [`SlipAccumulator`].
+/// The internal buffer of `N` bytes needs to hold the decoded message and its two byte
+/// frame check sequence. Frames with a bad frame check sequence are reported as
+/// [`FeedResult::DeserError`].
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{to_slice_hdlc, FeedResult, HdlcAccumulator};
+///
+/// let mut buf = [0u8; 32];
+/// let frame = to_slice_hdlc(&(0x7Eu8, 0x7Du8), &mut buf).unwrap();
+///
+/// let mut acc: HdlcAccumulator<32> = HdlcAccumulator::new();
+/// let (first, second) = frame.split_at(3);
+///
+/// assert!(matches!(acc.feed::<(u8, u8)>(first), FeedResult::Consumed));
+/// match acc.feed::<(u8, u8)>(second) {
+///     FeedResult::Success { data, remaining } => {
+///         assert_eq!(data, (0x7E, 0x7D));
+///         assert!(remaining.is_empty());
+///     }
+///     _ => panic!(),
+/// }
+/// ```
+///
+/// [`Hdlc`]: flavors/struct.Hdlc.html
+pub type HdlcAccumulator<const N: usize, M = ()> = Accumulator<decoders::Hdlc, N, M>;
+
+impl<const N: usize> HdlcAccumulator<N> {
+    /// Create a new accumulator.
+    pub const fn new() -> Self {
+        Self::with_decoder(decoders::Hdlc::new())
+    }
+}
+
+/// An accumulator used to collect chunked, framed data and deserialize it, using a growable
+/// buffer on the heap.
+///
+/// This works like the [`Accumulator`], but rather than choosing a fixed buffer size up front,
+/// the buffer grows as needed, up to `max_len` bytes per decoded frame.
+///
+/// This type is only available when the (non-default) `alloc` feature is active
+#[cfg(feature = "alloc")]
+pub struct AllocAccumulator<D, M = ()> {
+    buf: Vec<u8>,
+    max_len: usize,
+    framer: Framer<D, M>,
+}
+
+#[cfg(feature = "alloc")]
+impl<D> AllocAccumulator<D> {
+    /// Create a new accumulator using the given decoder, which accepts frames of up to
+    /// `max_len` decoded bytes. No memory is allocated until data is fed.
+    pub const fn with_decoder(decoder: D, max_len: usize) -> Self {
+        Self::with_monitor(decoder, max_len, ())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D, M> AllocAccumulator<D, M> {
+    /// Create a new accumulator like [`with_decoder`](AllocAccumulator::with_decoder), which
+    /// reports the received frames to `monitor`.
+    pub const fn with_monitor(decoder: D, max_len: usize, monitor: M) -> Self {
+        AllocAccumulator {
+            buf: Vec::new(),
+            max_len,
+            framer: Framer::new(decoder, monitor),
+        }
+    }
+
+    /// Get the monitor of this accumulator.
+    pub fn monitor(&self) -> &M {
+        &self.framer.monitor
+    }
+
+    /// Get the monitor of this accumulator mutably, for example to reset it.
+    pub fn monitor_mut(&mut self) -> &mut M {
+        &mut self.framer.monitor
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: FrameDecoder, M: FrameMonitor> AllocAccumulator<D, M> {
+    /// Appends data to the internal buffer and attempts to deserialize the accumulated data into
+    /// `T`.
+    pub fn feed<'a, T>(&mut self, input: &'a [u8]) -> FeedResult<'a, T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.feed_ref(input)
+    }
+
+    /// Appends data to the internal buffer and attempts to deserialize the accumulated data into
+    /// `T`, which may borrow from the internal buffer. See [`Accumulator::feed_ref`].
+    pub fn feed_ref<'de, 'a, T>(&'de mut self, input: &'a [u8]) -> FeedResult<'a, T>
+    where
+        T: Deserialize<'de>,
+    {
+        let (buf, max_len) = (&mut self.buf, self.max_len);
+        let frame = self.framer.decode(input, |idx, byte| {
+            if idx >= max_len {
+                return false;
+            }
+            match buf.get_mut(idx) {
+                Some(slot) => *slot = byte,
+                None => buf.push(byte),
+            }
+            true
+        });
+        self.framer.finish(frame, &mut self.buf)
+    }
+}
+
+/// An accumulator used to collect chunked COBS data and deserialize it, using a growable
+/// buffer on the heap.
+///
+/// This is an [`AllocAccumulator`] using the [`Cobs`](decoders::Cobs) decoder. It works like the
+/// [`CobsAccumulator`], but rather than choosing a fixed buffer size up front, the buffer grows
+/// as needed, up to `max_len` bytes per decoded frame. Frames longer than that are discarded up
+/// to the next zero byte, and reported as [`FeedResult::OverFull`], so a misbehaving sender can
+/// not exhaust memory.
+///
+/// This type is only available when the (non-default) `alloc` feature is active
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{to_slice_cobs, AllocCobsAccumulator, FeedResult};
+///
+/// let mut buf = [0u8; 1100];
+/// let frame = to_slice_cobs(&[0xAAu8; 1024][..], &mut buf).unwrap();
+///
+/// let mut acc = AllocCobsAccumulator::new(4096);
+/// let (first, second) = frame.split_at(700);
+///
+/// assert!(matches!(acc.feed_ref::<&[u8]>(first), FeedResult::Consumed));
+/// match acc.feed_ref::<&[u8]>(second) {
+///     FeedResult::Success { data, remaining } => {
+///         assert_eq!(data, &[0xAA; 1024][..]);
+///         assert!(remaining.is_empty());
+///     }
+///     _ => panic!(),
+/// }
+/// ```
+#[cfg(feature = "alloc")]
+pub type AllocCobsAccumulator<M = ()> = AllocAccumulator<decoders::Cobs, M>;
+
+#[cfg(feature = "alloc")]
+impl AllocCobsAccumulator {
+    /// Create a new accumulator, which accepts frames of up to `max_len` decoded bytes.
+    /// No memory is allocated until data is fed.
+    pub const fn new(max_len: usize) -> Self {
+        Self::with_decoder(decoders::Cobs::new(), max_len)
+    }
+}
+
+/// Receives notifications about the frames received by an accumulator.
+///
+/// This may be used to monitor the health of a link, or to log discarded frames. All methods
+/// do nothing by default. A monitor can carry any state it needs, and is accessed with
+/// [`Accumulator::monitor()`]. The `()` monitor, which accumulators use by default, ignores
+/// all frames. Two monitors can be combined as a tuple, and `&mut` references to monitors are
+/// monitors as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{decoders, CobsAccumulator, FeedResult, FrameError, FrameMonitor};
+///
+/// #[derive(Default)]
+/// struct LastError(Option<FrameError>);
+///
+/// impl FrameMonitor for LastError {
+///     fn frame_discarded(&mut self, error: &FrameError, _frame: &[u8]) {
+///         self.0 = Some(error.clone());
+///     }
+/// }
+///
+/// let mut acc: CobsAccumulator<4, LastError> = CobsAccumulator::default();
+/// let input = &[0x06, 0x01, 0x02, 0x03, 0x04, 0x05, 0x00];
+///
+/// assert!(matches!(acc.feed::<()>(input), FeedResult::OverFull(_)));
+/// assert_eq!(acc.monitor().0, Some(FrameError::OverFull));
+/// ```
+pub trait FrameMonitor {
+    /// Called with the number of input bytes that were consumed as part of the current frame,
+    /// including any bytes of the framing itself. This may be called several times per frame.
+    fn consumed(&mut self, _len: usize) {}
+
+    /// Called when a frame was deserialized successfully.
+    fn frame_ok(&mut self) {}
+
+    /// Called with each frame that was discarded, along with the bytes of it that were collected
+    /// in the buffer, as decoded by the frame decoder. For frames that did not fit, this is only
+    /// the start of the frame.
+    fn frame_discarded(&mut self, _error: &FrameError, _frame: &[u8]) {}
+}
+
+impl FrameMonitor for () {}
+
+impl<A: FrameMonitor, B: FrameMonitor> FrameMonitor for (A, B) {
+    fn consumed(&mut self, len: usize) {
+        self.0.consumed(len);
+        self.1.consumed(len);
+    }
+
+    fn frame_ok(&mut self) {
+        self.0.frame_ok();
+        self.1.frame_ok();
+    }
+
+    fn frame_discarded(&mut self, error: &FrameError, frame: &[u8]) {
+        self.0.frame_discarded(error, frame);
+        self.1.frame_discarded(error, frame);
+    }
+}
+
+impl<M: FrameMonitor + ?Sized> FrameMonitor for &mut M {
+    fn consumed(&mut self, len: usize) {
+        (**self).consumed(len);
+    }
+
+    fn frame_ok(&mut self) {
+        (**self).frame_ok();
+    }
+
+    fn frame_discarded(&mut self, error: &FrameError, frame: &[u8]) {
+        (**self).frame_discarded(error, frame);
+    }
+}
+
+/// Statistics about the frames received by an accumulator, as collected by the
+/// [`StatsMonitor`].
+///
+/// All counters wrap around on overflow.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct AccumulatorStats {
+    /// Number of frames that were deserialized successfully
+    pub frames_ok: u32,
+
+    /// Number of frames that did not fit into the buffer, see [`FeedResult::OverFull`]
+    pub overflows: u32,
+
+    /// Number of frames that were invalid, or could not be deserialized, see
+    /// [`FeedResult::DeserError`]
+    pub decode_errors: u32,
+
+    /// Number of input bytes that were discarded as part of a failed frame, including any
+    /// bytes of the framing itself
+    pub bytes_discarded: u32,
+}
+
+/// A [`FrameMonitor`] which counts the received frames in [`AccumulatorStats`].
+///
+/// See [`Accumulator::with_monitor()`] for an example.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub struct StatsMonitor {
+    stats: AccumulatorStats,
+    // Number of input bytes of the current frame
+    raw: usize,
+}
+
+impl StatsMonitor {
+    /// Create a new monitor, with all statistics set to zero.
+    pub const fn new() -> Self {
+        StatsMonitor {
+            stats: AccumulatorStats {
+                frames_ok: 0,
+                overflows: 0,
+                decode_errors: 0,
+                bytes_discarded: 0,
+            },
+            raw: 0,
+        }
+    }
+
+    /// Get the statistics about the frames received so far.
+    pub fn stats(&self) -> AccumulatorStats {
+        self.stats
+    }
+
+    /// Reset all statistics to zero.
+    pub fn reset(&mut self) {
+        self.stats = AccumulatorStats::default();
+    }
+}
+
+impl FrameMonitor for StatsMonitor {
+    fn consumed(&mut self, len: usize) {
+        self.raw += len;
+    }
+
+    fn frame_ok(&mut self) {
+        self.raw = 0;
+        self.stats.frames_ok = self.stats.frames_ok.wrapping_add(1);
+    }
+
+    fn frame_discarded(&mut self, error: &FrameError, _frame: &[u8]) {
+        let raw = core::mem::take(&mut self.raw);
+        match error {
+            FrameError::OverFull => {
+                self.stats.overflows = self.stats.overflows.wrapping_add(1);
+            }
+            FrameError::Invalid | FrameError::Deserialize(_) => {
+                self.stats.decode_errors = self.stats.decode_errors.wrapping_add(1);
+            }
+        }
+        self.stats.bytes_discarded = self.stats.bytes_discarded.wrapping_add(raw as u32);
+    }
+}
+
+/// The reason a frame was discarded by an accumulator, which is passed to its [`FrameMonitor`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub enum FrameError {
+    /// The frame did not fit into the buffer
+    OverFull,
+
+    /// The frame decoder reported an invalid frame, e.g. due to a bad escape sequence
+    Invalid,
+
+    /// The frame was rejected by the frame decoder, such as for a bad checksum, or could not
+    /// be deserialized
+    Deserialize(Error),
+}
+
+impl From<FrameError> for Error {
+    fn from(error: FrameError) -> Self {
+        match error {
+            FrameError::OverFull => Error::DeserializeBufferFull,
+            FrameError::Invalid => Error::DeserializeBadEncoding,
+            FrameError::Deserialize(error) => error,
+        }
+    }
+}
+
+/// The framing state shared by all accumulators
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+struct Framer<D, M> {
+    decoder: D,
+    // Number of decoded bytes of the current frame
+    idx: usize,
+    // The current frame did not fit, discarding until the end of the frame
+    overfull: bool,
+    monitor: M,
+}
+
+/// The outcome of decoding input with a [`Framer`]. Complete frames take up `len` bytes of
+/// the buffer, and contain the remaining input.
+enum Frame<'a> {
+    /// Consumed all input, the frame is not complete yet
+    Pending,
+    /// The frame did not fit into the buffer
+    OverFull { len: usize, remaining: &'a [u8] },
+    /// The frame was invalid
+    Invalid { len: usize, remaining: &'a [u8] },
+    /// The frame is complete
+    Complete { len: usize, remaining: &'a [u8] },
+}
+
+impl<D, M> Framer<D, M> {
+    const fn new(decoder: D, monitor: M) -> Self {
+        Framer {
+            decoder,
+            idx: 0,
+            overfull: false,
+            monitor,
+        }
+    }
+}
+
+impl<D: FrameDecoder, M: FrameMonitor> Framer<D, M> {
+    /// Decode input up to the end of the next frame. Decoded bytes are handed to `store`
+    /// along with their position in the frame, which returns `false` if they do not fit.
+    fn decode<'a>(
+        &mut self,
+        input: &'a [u8],
+        mut store: impl FnMut(usize, u8) -> bool,
+    ) -> Frame<'a> {
+        for (i, &byte) in input.iter().enumerate() {
+            let valid = match self.decoder.decode(byte) {
+                Decoded::Nothing => continue,
+                Decoded::Byte(byte) => {
+                    self.push(byte, &mut store);
+                    continue;
+                }
+                Decoded::Last(byte) => {
+                    self.push(byte, &mut store);
+                    true
+                }
+                Decoded::End => true,
+                Decoded::Invalid => false,
+            };
+
+            let remaining = &input[i + 1..];
+            let len = core::mem::take(&mut self.idx);
+            self.monitor.consumed(i + 1);
+            return if core::mem::take(&mut self.overfull) {
+                Frame::OverFull { len, remaining }
+            } else if valid {
+                Frame::Complete { len, remaining }
+            } else {
+                Frame::Invalid { len, remaining }
+            };
+        }
+
+        self.monitor.consumed(input.len());
+        Frame::Pending
+    }
+
+    /// Report a discarded frame to the monitor
+    fn discard(&mut self, error: FrameError, frame: &[u8]) -> FrameError {
+        self.monitor.frame_discarded(&error, frame);
+        error
+    }
+
+    /// Store a decoded byte, or start discarding the frame if it does not fit.
+    fn push(&mut self, byte: u8, store: &mut impl FnMut(usize, u8) -> bool) {
+        if !self.overfull {
+            if store(self.idx, byte) {
+                self.idx += 1;
+            } else {
+                self.overfull = true;
+            }
+        }
+    }
+
+    /// Check a complete frame, held in the start of `buf`, and deserialize it.
+    fn finish<'de, 'a, T>(&mut self, frame: Frame<'a>, buf: &'de mut [u8]) -> FeedResult<'a, T>
+    where
+        T: Deserialize<'de>,
+    {
+        match self.complete(frame, buf) {
+            None => FeedResult::Consumed,
+            Some((Ok(data), remaining)) => FeedResult::Success { data, remaining },
+            Some((Err(FrameError::OverFull), remaining)) => FeedResult::OverFull(remaining),
+            Some((Err(_), remaining)) => FeedResult::DeserError(remaining),
+        }
+    }
+
+    /// Like [`Framer::finish`], but returns the reason the frame was discarded. Returns `None`
+    /// if the frame is not complete yet.
+    #[allow(clippy::type_complexity)]
+    fn complete<'de, 'a, T>(
+        &mut self,
+        frame: Frame<'a>,
+        buf: &'de mut [u8],
+    ) -> Option<(core::result::Result<T, FrameError>, &'a [u8])>
+    where
+        T: Deserialize<'de>,
+    {
+        let (len, remaining) = match frame {
+            Frame::Pending => return None,
+            Frame::OverFull { len, remaining } => {
+                let error = self.discard(FrameError::OverFull, &buf[..len]);
+                return Some((Err(error), remaining));
+            }
+            Frame::Invalid { len, remaining } => {
+                let error = self.discard(FrameError::Invalid, &buf[..len]);
+                return Some((Err(error), remaining));
+            }
+            Frame::Complete { len, remaining } => (len, remaining),
+        };
+
+        let frame = &mut buf[..len];
+        let range: Result<_> = self.decoder.finish(frame);
+        let frame: &'de [u8] = frame;
+        match range.and_then(|range| crate::from_bytes::<T>(&frame[range])) {
+            Ok(t) => {
+                self.monitor.frame_ok();
+                Some((Ok(t), remaining))
+            }
+            Err(e) => {
+                let error = self.discard(FrameError::Deserialize(e), frame);
+                Some((Err(error), remaining))
+            }
+        }
+    }
+}
+
+#[test]
+fn loop_test() {
+    #[derive(serde::Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Demo {
+        a: u32,
+        b: u8,
+    }
+
+    let mut raw_buf = [0u8; 64];
+    let mut cobs_buf: CobsAccumulator<64> = CobsAccumulator::new();
+
+    let ser = crate::to_slice_cobs(&Demo { a: 10, b: 20 }, &mut raw_buf).unwrap();
+
+    if let FeedResult::Success { data, remaining } = cobs_buf.feed(ser) {
+        assert_eq!(Demo { a: 10, b: 20 }, data);
+        assert_eq!(remaining.len(), 0);
+    } else {
+        panic!()
+    }
+}
+
+#[test]
+fn loop_test_ref() {
+    #[derive(serde::Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Demo<'a> {
+        a: u32,
+        b: &'a str,
+        c: &'a [u8],
+    }
+
+    let mut raw_buf = [0u8; 64];
+    let mut cobs_buf: CobsAccumulator<64> = CobsAccumulator::new();
+
+    let demo = Demo {
+        a: 10,
+        b: "borrowed",
+        c: &[0x00, 0x01, 0x00],
+    };
+    let ser = crate::to_slice_cobs(&demo, &mut raw_buf).unwrap();
+    let (first, second) = ser.split_at(5);
+
+    assert!(matches!(
+        cobs_buf.feed_ref::<Demo>(first),
+        FeedResult::Consumed
+    ));
+    if let FeedResult::Success { data, remaining } = cobs_buf.feed_ref::<Demo>(second) {
+        assert_eq!(demo, data);
+        assert_eq!(remaining.len(), 0);
+    } else {
+        panic!()
+    }
+}
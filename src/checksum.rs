@@ -0,0 +1,47 @@
+//! Support for checksumming messages with the [`crc`](https://crates.io/crates/crc) crate.
+
+use crc::Digest;
+
+/// A running CRC calculation, which may be used with the [`Crc`] serialization flavor and the
+/// [`from_bytes_crc()`] family of functions.
+///
+/// This trait is implemented for the [`crc::Digest`] types of CRC-8, CRC-16, CRC-32 and CRC-64
+/// algorithms. The checksum is placed on the wire as `SIZE` bytes, in little endian order.
+///
+/// This type is only available when the (non-default) `use-crc` feature is active.
+///
+/// [`Crc`]: flavors/struct.Crc.html
+/// [`from_bytes_crc()`]: fn.from_bytes_crc.html
+pub trait CrcDigest {
+    /// The size of the checksum on the wire, in bytes
+    const SIZE: usize;
+
+    /// Add the given bytes to the checksum
+    fn update(&mut self, data: &[u8]);
+
+    /// Complete the calculation, returning the checksum. Only the lowest
+    /// `SIZE` bytes of the returned value are used.
+    fn finalize(self) -> u64;
+}
+
+macro_rules! impl_crc_digest {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<'a> CrcDigest for Digest<'a, $ty> {
+                const SIZE: usize = core::mem::size_of::<$ty>();
+
+                #[inline]
+                fn update(&mut self, data: &[u8]) {
+                    Digest::<'a, $ty>::update(self, data)
+                }
+
+                #[inline]
+                fn finalize(self) -> u64 {
+                    Digest::<'a, $ty>::finalize(self).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_crc_digest!(u8, u16, u32, u64);
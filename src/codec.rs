@@ -0,0 +1,246 @@
+//! A [`tokio_util::codec`] for COBS framed postcard messages.
+
+use crate::de::from_bytes_cobs;
+use crate::error::Error;
+use crate::ser::flavors::{Cobs, SerFlavor};
+use crate::ser::serialize_with_flavor;
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io;
+use tokio_util::bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A codec which encodes messages of type `T` with postcard, and frames them with COBS,
+/// as produced by [`to_stdvec_cobs()`](crate::to_stdvec_cobs). Together with
+/// `tokio_util::codec::Framed`, it turns a byte stream, such as a `TcpStream`, into a `Sink`
+/// and `Stream` of messages.
+///
+/// Frames longer than the maximum frame length, counted without the terminating zero byte,
+/// are rejected. When decoding, such a frame is discarded up to its end, so the buffered input
+/// stays bounded. Empty frames are ignored.
+///
+/// Errors are returned from the codec as a [`CodecError`]. After an error, the codec
+/// continues with the next frame, but note that `Framed` ends the stream after the first
+/// error.
+///
+/// This type is only available when the (non-default) `tokio-util` feature is active.
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{CodecError, Error, PostcardCobsCodec};
+/// use tokio_util::{
+///     bytes::BytesMut,
+///     codec::{Decoder, Encoder},
+/// };
+///
+/// let mut codec = PostcardCobsCodec::<(u8, u16)>::new(16);
+/// let mut buf = BytesMut::new();
+/// codec.encode((1, 2), &mut buf).unwrap();
+/// buf.extend_from_slice(&[0x02, 0xFF, 0x00]);
+/// codec.encode((3, 4), &mut buf).unwrap();
+///
+/// assert_eq!(codec.decode(&mut buf).unwrap(), Some((1, 2)));
+/// assert!(matches!(
+///     codec.decode(&mut buf),
+///     Err(CodecError::Postcard(Error::DeserializeUnexpectedEnd))
+/// ));
+/// assert_eq!(codec.decode(&mut buf).unwrap(), Some((3, 4)));
+/// assert_eq!(codec.decode(&mut buf).unwrap(), None);
+/// ```
+pub struct PostcardCobsCodec<T> {
+    max_frame_len: usize,
+    // Bytes of the buffer that are known not to contain a zero byte
+    searched: usize,
+    // A frame that was too long is being discarded
+    discarding: bool,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> PostcardCobsCodec<T> {
+    /// Create a new codec, for frames of up to `max_frame_len` bytes, not counting the
+    /// terminating zero byte.
+    pub fn new(max_frame_len: usize) -> Self {
+        PostcardCobsCodec {
+            max_frame_len,
+            searched: 0,
+            discarding: false,
+            _t: PhantomData,
+        }
+    }
+
+    /// The maximum length of a frame, not counting the terminating zero byte.
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+}
+
+impl<T> Clone for PostcardCobsCodec<T> {
+    fn clone(&self) -> Self {
+        PostcardCobsCodec {
+            max_frame_len: self.max_frame_len,
+            searched: self.searched,
+            discarding: self.discarding,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PostcardCobsCodec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PostcardCobsCodec")
+            .field("max_frame_len", &self.max_frame_len)
+            .field("discarding", &self.discarding)
+            .finish()
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for PostcardCobsCodec<T> {
+    type Item = T;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, CodecError> {
+        loop {
+            let end = match src[self.searched..].iter().position(|b| *b == 0) {
+                Some(pos) => self.searched + pos,
+                None if self.discarding => {
+                    src.clear();
+                    self.searched = 0;
+                    return Ok(None);
+                }
+                None if src.len() > self.max_frame_len => {
+                    src.clear();
+                    self.searched = 0;
+                    self.discarding = true;
+                    return Err(Error::DeserializeBufferFull.into());
+                }
+                None => {
+                    self.searched = src.len();
+                    return Ok(None);
+                }
+            };
+
+            self.searched = 0;
+            if core::mem::take(&mut self.discarding) || end == 0 {
+                src.advance(end + 1);
+                continue;
+            }
+            if end > self.max_frame_len {
+                src.advance(end + 1);
+                return Err(Error::DeserializeBufferFull.into());
+            }
+
+            let mut frame = src.split_to(end + 1);
+            return Ok(Some(from_bytes_cobs(&mut frame)?));
+        }
+    }
+}
+
+impl<T: Serialize> Encoder<T> for PostcardCobsCodec<T> {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let start = dst.len();
+        let frame = Frame {
+            buf: dst,
+            start,
+            max_len: self.max_frame_len.saturating_add(1),
+        };
+        let result = Cobs::try_new(frame).and_then(|cobs| serialize_with_flavor(&item, cobs));
+        if let Err(e) = result {
+            // Remove the partially encoded frame
+            dst.truncate(start);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+/// A storage flavor appending a frame of up to `max_len` bytes, including the terminating
+/// zero byte, to a `BytesMut`. Indices are relative to the start of the frame.
+struct Frame<'a> {
+    buf: &'a mut BytesMut,
+    start: usize,
+    max_len: usize,
+}
+
+impl SerFlavor for Frame<'_> {
+    type Output = ();
+
+    #[inline(always)]
+    fn try_extend(&mut self, data: &[u8]) -> Result<(), ()> {
+        if self.buf.len() - self.start + data.len() > self.max_len {
+            return Err(());
+        }
+        self.buf.extend_from_slice(data);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn try_push(&mut self, data: u8) -> Result<(), ()> {
+        self.try_extend(&[data])
+    }
+
+    fn release(self) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+impl Index<usize> for Frame<'_> {
+    type Output = u8;
+
+    fn index(&self, idx: usize) -> &u8 {
+        &self.buf[self.start + idx]
+    }
+}
+
+impl IndexMut<usize> for Frame<'_> {
+    fn index_mut(&mut self, idx: usize) -> &mut u8 {
+        &mut self.buf[self.start + idx]
+    }
+}
+
+/// The error type of the [`PostcardCobsCodec`].
+///
+/// This type is only available when the (non-default) `tokio-util` feature is active.
+#[derive(Debug)]
+pub enum CodecError {
+    /// Reading from or writing to the underlying stream failed.
+    Io(io::Error),
+
+    /// A message could not be encoded or decoded. Frames that are too long are reported as
+    /// [`Error::SerializeBufferFull`] or [`Error::DeserializeBufferFull`].
+    Postcard(Error),
+}
+
+impl From<io::Error> for CodecError {
+    fn from(e: io::Error) -> Self {
+        CodecError::Io(e)
+    }
+}
+
+impl From<Error> for CodecError {
+    fn from(e: Error) -> Self {
+        CodecError::Postcard(e)
+    }
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Io(e) => e.fmt(f),
+            CodecError::Postcard(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodecError::Io(e) => Some(e),
+            CodecError::Postcard(e) => Some(e),
+        }
+    }
+}
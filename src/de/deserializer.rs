@@ -6,71 +6,211 @@ use serde::de::{
     // EnumAccess, MapAccess, VariantAccess
 };
 
-use crate::error::{Error, Result};
-use crate::varint::VarintUsize;
-
-/// A structure for deserializing a postcard message. For now, Deserializer does not
-/// implement the same Flavor interface as the serializer does, as messages are typically
-/// easier to deserialize in place. This may change in the future for consistency, or
-/// to support items that cannot be deserialized in-place, such as compressed message types
-pub struct Deserializer<'de> {
-    // This string starts with the input data and characters are truncated off
-    // the beginning as data is parsed.
-    pub(crate) input: &'de [u8],
+use core::marker::PhantomData;
+
+use crate::de::flavors::{DeFlavor, Slice};
+use crate::error::{DetailedError, Error, PathSegment, Result};
+use crate::varint::*;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A `serde` compatible deserializer, generic over "Flavors" of deserializing plugins.
+///
+/// It should rarely be necessary to directly use this type unless you are implementing your
+/// own [`DeFlavor`], or need access to the remaining data once deserialization is done.
+///
+/// See the docs for [`DeFlavor`] for more information about "flavors" of deserialization
+///
+/// [`DeFlavor`]: de_flavors/trait.DeFlavor.html
+pub struct Deserializer<'de, F: DeFlavor<'de> = Slice<'de>> {
+    // This is the Flavor(s) that will be used to provide the bytes
+    // to be deserialized
+    flavor: F,
+
+    /// How integers wider than a byte are expected to be encoded. This must match
+    /// the encoding used by the serializer. See [`IntEncoding`] for details.
+    ///
+    /// [`IntEncoding`]: enum.IntEncoding.html
+    pub int_encoding: IntEncoding,
+
+    // The path to the value currently being decoded, if it is being recorded
+    #[cfg(feature = "alloc")]
+    path: Option<Vec<PathSegment>>,
+
+    _plt: PhantomData<&'de ()>,
 }
 
-impl<'de> Deserializer<'de> {
+impl<'de> Deserializer<'de, Slice<'de>> {
     /// Obtain a Deserializer from a slice of bytes
     pub fn from_bytes(input: &'de [u8]) -> Self {
-        Deserializer { input }
+        Deserializer::from_flavor(Slice::new(input))
+    }
+
+    /// Obtain a Deserializer from a slice of bytes, where all integers wider
+    /// than a byte are encoded as varints
+    pub fn from_bytes_varint(input: &'de [u8]) -> Self {
+        let mut deserializer = Deserializer::from_bytes(input);
+        deserializer.int_encoding = IntEncoding::Varint;
+        deserializer
+    }
+
+    /// Turn the outcome of deserializing from `input` into a [`DetailedError`] on
+    /// failure, or the unused portion of `input` on success.
+    pub(crate) fn finalize_detailed<T>(
+        self,
+        input: &'de [u8],
+        result: Result<T>,
+    ) -> core::result::Result<(T, &'de [u8]), DetailedError> {
+        // The remaining input of the `Slice` flavor is always a tail of the original input
+        let offset = input.len() - self.flavor.size_hint().unwrap_or(0);
+        match result {
+            Ok(t) => Ok((t, &input[offset..])),
+            Err(kind) => Err(DetailedError {
+                kind,
+                offset,
+                #[cfg(feature = "alloc")]
+                path: self.path.unwrap_or_default(),
+            }),
+        }
     }
 }
 
-impl<'de> Deserializer<'de> {
-    fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
-        if self.input.len() >= ct {
-            let (a, b) = self.input.split_at(ct);
-            self.input = b;
-            Ok(a)
-        } else {
-            Err(Error::DeserializeUnexpectedEnd)
+impl<'de, F: DeFlavor<'de>> Deserializer<'de, F> {
+    /// Obtain a Deserializer from the given flavor
+    pub fn from_flavor(flavor: F) -> Self {
+        Deserializer {
+            flavor,
+            int_encoding: IntEncoding::Fixed,
+            #[cfg(feature = "alloc")]
+            path: None,
+            _plt: PhantomData,
+        }
+    }
+
+    /// Start recording the path to the value being decoded, for use in a [`DetailedError`]
+    #[cfg(feature = "alloc")]
+    pub(crate) fn record_path(&mut self) {
+        self.path = Some(Vec::new());
+    }
+
+    /// Note that decoding of the given nested value is starting. This is a no-op unless
+    /// the path is being recorded.
+    #[inline]
+    fn enter(&mut self, segment: PathSegment) {
+        #[cfg(feature = "alloc")]
+        if let Some(path) = self.path.as_mut() {
+            path.push(segment);
         }
+        #[cfg(not(feature = "alloc"))]
+        let _ = segment;
+    }
+
+    /// Note that decoding of the innermost nested value has succeeded. As errors are never
+    /// recovered from, the path is left as-is on failure, and points to the failing value.
+    #[inline]
+    fn leave(&mut self) {
+        #[cfg(feature = "alloc")]
+        if let Some(path) = self.path.as_mut() {
+            path.pop();
+        }
+    }
+
+    /// Return the remaining (unused) data of the flavor, once deserialization is complete
+    pub fn finalize(self) -> Result<F::Remainder> {
+        self.flavor.finalize()
+    }
+}
+
+macro_rules! impl_try_take_varint {
+    ($($name:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $name(&mut self) -> Result<$ty> {
+                let mut out: $ty = 0;
+                for i in 0..varint_max::<$ty>() {
+                    let val = self.flavor.pop()?;
+                    let carry = (val & 0x7F) as $ty;
+                    out |= carry << (7 * i);
+
+                    if (val & 0x80) == 0 {
+                        if i == varint_max::<$ty>() - 1 && val > max_of_last_byte::<$ty>() {
+                            return Err(Error::DeserializeBadVarint);
+                        }
+                        return Ok(out);
+                    }
+                }
+                Err(Error::DeserializeBadVarint)
+            }
+        )*
+    };
+}
+
+impl<'de, F: DeFlavor<'de>> Deserializer<'de, F> {
+    #[inline]
+    fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+        self.flavor.try_take_n(ct)
     }
 
-    fn try_take_varint(&mut self) -> Result<usize> {
+    #[inline]
+    pub(crate) fn try_take_n_temp(&mut self, ct: usize) -> Result<&[u8]> {
+        self.flavor.try_take_n_temp(ct)
+    }
+
+    #[inline]
+    fn try_take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.try_take_n_temp(N)?);
+        Ok(buf)
+    }
+
+    pub(crate) fn try_take_varint(&mut self) -> Result<usize> {
+        let mut out = 0usize;
         for i in 0..VarintUsize::varint_usize_max() {
-            let val = self.input.get(i).ok_or(Error::DeserializeUnexpectedEnd)?;
+            let val = self.flavor.pop()?;
+            out |= ((val & 0x7F) as usize) << (7 * i);
             if (val & 0x80) == 0 {
-                let (a, b) = self.input.split_at(i + 1);
-                self.input = b;
-                let mut out = 0usize;
-                for byte in a.iter().rev() {
-                    out <<= 7;
-                    out |= (byte & 0x7F) as usize;
-                }
                 return Ok(out);
             }
         }
 
         Err(Error::DeserializeBadVarint)
     }
+
+    impl_try_take_varint! {
+        try_take_varint_u16: u16,
+        try_take_varint_u32: u32,
+        try_take_varint_u64: u64,
+        try_take_varint_u128: u128,
+    }
 }
 
-struct SeqAccess<'a, 'b: 'a> {
-    deserializer: &'a mut Deserializer<'b>,
+struct SeqAccess<'a, 'b: 'a, F: DeFlavor<'b>> {
+    deserializer: &'a mut Deserializer<'b, F>,
     len: usize,
+    // The names of the elements, for structs
+    fields: &'static [&'static str],
+    idx: usize,
 }
 
-impl<'a, 'b: 'a> serde::de::SeqAccess<'b> for SeqAccess<'a, 'b> {
+impl<'a, 'b: 'a, F: DeFlavor<'b>> serde::de::SeqAccess<'b> for SeqAccess<'a, 'b, F> {
     type Error = Error;
 
     fn next_element_seed<V: DeserializeSeed<'b>>(&mut self, seed: V) -> Result<Option<V::Value>> {
         if self.len > 0 {
             self.len -= 1;
-            Ok(Some(DeserializeSeed::deserialize(
-                seed,
-                &mut *self.deserializer,
-            )?))
+            let segment = match self.fields.get(self.idx) {
+                Some(name) => PathSegment::Field(name),
+                None => PathSegment::Index(self.idx),
+            };
+            self.idx += 1;
+
+            self.deserializer.enter(segment);
+            let value = DeserializeSeed::deserialize(seed, &mut *self.deserializer)?;
+            self.deserializer.leave();
+            Ok(Some(value))
         } else {
             Ok(None)
         }
@@ -81,28 +221,33 @@ impl<'a, 'b: 'a> serde::de::SeqAccess<'b> for SeqAccess<'a, 'b> {
     }
 }
 
-struct MapAccess<'a, 'b: 'a> {
-    deserializer: &'a mut Deserializer<'b>,
+struct MapAccess<'a, 'b: 'a, F: DeFlavor<'b>> {
+    deserializer: &'a mut Deserializer<'b, F>,
     len: usize,
+    idx: usize,
 }
 
-impl<'a, 'b: 'a> serde::de::MapAccess<'b> for MapAccess<'a, 'b> {
+impl<'a, 'b: 'a, F: DeFlavor<'b>> serde::de::MapAccess<'b> for MapAccess<'a, 'b, F> {
     type Error = Error;
 
     fn next_key_seed<K: DeserializeSeed<'b>>(&mut self, seed: K) -> Result<Option<K::Value>> {
         if self.len > 0 {
             self.len -= 1;
-            Ok(Some(DeserializeSeed::deserialize(
-                seed,
-                &mut *self.deserializer,
-            )?))
+            self.deserializer.enter(PathSegment::Index(self.idx));
+            let key = DeserializeSeed::deserialize(seed, &mut *self.deserializer)?;
+            self.deserializer.leave();
+            Ok(Some(key))
         } else {
             Ok(None)
         }
     }
 
     fn next_value_seed<V: DeserializeSeed<'b>>(&mut self, seed: V) -> Result<V::Value> {
-        DeserializeSeed::deserialize(seed, &mut *self.deserializer)
+        self.deserializer.enter(PathSegment::Index(self.idx));
+        let value = DeserializeSeed::deserialize(seed, &mut *self.deserializer)?;
+        self.deserializer.leave();
+        self.idx += 1;
+        Ok(value)
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -110,7 +255,7 @@ impl<'a, 'b: 'a> serde::de::MapAccess<'b> for MapAccess<'a, 'b> {
     }
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de, F: DeFlavor<'de>> de::Deserializer<'de> for &mut Deserializer<'de, F> {
     type Error = Error;
 
     fn is_human_readable(&self) -> bool {
@@ -131,7 +276,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let val = match self.try_take_n(1)?[0] {
+        let val = match self.flavor.pop()? {
             0 => false,
             1 => true,
             _ => return Err(Error::DeserializeBadBool),
@@ -143,97 +288,109 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 1];
-        buf[..].copy_from_slice(self.try_take_n(1)?);
-        visitor.visit_i8(i8::from_le_bytes(buf))
+        visitor.visit_i8(self.flavor.pop()? as i8)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 2];
-        buf[..].copy_from_slice(self.try_take_n(2)?);
-        visitor.visit_i16(i16::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => i16::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => de_zig_zag_i16(self.try_take_varint_u16()?),
+        };
+        visitor.visit_i16(val)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 4];
-        buf[..].copy_from_slice(self.try_take_n(4)?);
-        visitor.visit_i32(i32::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => i32::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => de_zig_zag_i32(self.try_take_varint_u32()?),
+        };
+        visitor.visit_i32(val)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 8];
-        buf[..].copy_from_slice(self.try_take_n(8)?);
-        visitor.visit_i64(i64::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => i64::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => de_zig_zag_i64(self.try_take_varint_u64()?),
+        };
+        visitor.visit_i64(val)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 16];
-        buf[..].copy_from_slice(self.try_take_n(16)?);
-        visitor.visit_i128(i128::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => i128::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => de_zig_zag_i128(self.try_take_varint_u128()?),
+        };
+        visitor.visit_i128(val)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(self.try_take_n(1)?[0])
+        visitor.visit_u8(self.flavor.pop()?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 2];
-        buf[..].copy_from_slice(self.try_take_n(2)?);
-        visitor.visit_u16(u16::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => u16::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => self.try_take_varint_u16()?,
+        };
+        visitor.visit_u16(val)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 4];
-        buf[..].copy_from_slice(self.try_take_n(4)?);
-        visitor.visit_u32(u32::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => u32::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => self.try_take_varint_u32()?,
+        };
+        visitor.visit_u32(val)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 8];
-        buf[..].copy_from_slice(self.try_take_n(8)?);
-        visitor.visit_u64(u64::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => u64::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => self.try_take_varint_u64()?,
+        };
+        visitor.visit_u64(val)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let mut buf = [0u8; 16];
-        buf[..].copy_from_slice(self.try_take_n(16)?);
-        visitor.visit_u128(u128::from_le_bytes(buf))
+        let val = match self.int_encoding {
+            IntEncoding::Fixed => u128::from_le_bytes(self.try_take_array()?),
+            IntEncoding::Varint => self.try_take_varint_u128()?,
+        };
+        visitor.visit_u128(val)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let bytes = self.try_take_n(4)?;
-        let mut buf = [0u8; 4];
-        buf.copy_from_slice(bytes);
+        let buf = self.try_take_array()?;
         visitor.visit_f32(f32::from_bits(u32::from_le_bytes(buf)))
     }
 
@@ -241,9 +398,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let bytes = self.try_take_n(8)?;
-        let mut buf = [0u8; 8];
-        buf.copy_from_slice(bytes);
+        let buf = self.try_take_array()?;
         visitor.visit_f64(f64::from_bits(u64::from_le_bytes(buf)))
     }
 
@@ -255,12 +410,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         if sz > 4 {
             return Err(Error::DeserializeBadChar);
         }
-        let bytes: &'de [u8] = self.try_take_n(sz)?;
+        let bytes = self.try_take_n_temp(sz)?;
         // we pass the character through string conversion because
         // this handles transforming the array of code units to a 
         // codepoint. we can't use char::from_u32() because it expects
         // an already-processed codepoint.
-        let character = core::str::from_utf8(&bytes)
+        let character = core::str::from_utf8(bytes)
             .map_err(|_| Error::DeserializeBadChar)?
             .chars()
             .next()
@@ -283,7 +438,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_str(visitor)
+        // The visitor will make its own copy, so the bytes only need to be
+        // borrowed temporarily from the flavor
+        let sz = self.try_take_varint()?;
+        let bytes = self.try_take_n_temp(sz)?;
+        let str_sl = core::str::from_utf8(bytes).map_err(|_| Error::DeserializeBadUtf8)?;
+
+        visitor.visit_str(str_sl)
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
@@ -299,14 +460,16 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_bytes(visitor)
+        let sz = self.try_take_varint()?;
+        let bytes = self.try_take_n_temp(sz)?;
+        visitor.visit_bytes(bytes)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.try_take_n(1)?[0] {
+        match self.flavor.pop()? {
             0 => visitor.visit_none(),
             1 => visitor.visit_some(self),
             _ => Err(Error::DeserializeBadOption),
@@ -347,6 +510,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         visitor.visit_seq(SeqAccess {
             deserializer: self,
             len,
+            fields: &[],
+            idx: 0,
         })
     }
 
@@ -357,6 +522,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         visitor.visit_seq(SeqAccess {
             deserializer: self,
             len,
+            fields: &[],
+            idx: 0,
         })
     }
 
@@ -381,6 +548,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         visitor.visit_map(MapAccess {
             deserializer: self,
             len,
+            idx: 0,
         })
     }
 
@@ -393,19 +561,30 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_tuple(fields.len(), visitor)
+        visitor.visit_seq(SeqAccess {
+            deserializer: self,
+            len: fields.len(),
+            fields,
+            idx: 0,
+        })
     }
 
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(self)
+        let value = visitor.visit_enum(EnumAccess {
+            deserializer: &mut *self,
+            variants,
+        })?;
+        // Leave the variant entered by `EnumAccess::variant_seed()`
+        self.leave();
+        Ok(value)
     }
 
     // As a binary format, Postcard does not encode identifiers
@@ -426,7 +605,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 }
 
-impl<'de, 'a> serde::de::VariantAccess<'de> for &'a mut Deserializer<'de> {
+impl<'de, F: DeFlavor<'de>> serde::de::VariantAccess<'de> for &mut Deserializer<'de, F> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -446,20 +625,32 @@ impl<'de, 'a> serde::de::VariantAccess<'de> for &'a mut Deserializer<'de> {
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value> {
-        serde::de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+        serde::de::Deserializer::deserialize_struct(self, "", fields, visitor)
     }
 }
 
-impl<'de, 'a> serde::de::EnumAccess<'de> for &'a mut Deserializer<'de> {
+struct EnumAccess<'a, 'b: 'a, F: DeFlavor<'b>> {
+    deserializer: &'a mut Deserializer<'b, F>,
+    variants: &'static [&'static str],
+}
+
+impl<'a, 'b: 'a, F: DeFlavor<'b>> serde::de::EnumAccess<'b> for EnumAccess<'a, 'b, F> {
     type Error = Error;
-    type Variant = Self;
+    type Variant = &'a mut Deserializer<'b, F>;
 
-    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
-        let varint = self.try_take_varint()?;
+    fn variant_seed<V: DeserializeSeed<'b>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let varint = self.deserializer.try_take_varint()?;
         if varint > 0xFFFF_FFFF {
             return Err(Error::DeserializeBadEnum);
         }
-        let v = DeserializeSeed::deserialize(seed, (varint as u32).into_deserializer())?;
-        Ok((v, self))
+        let v = DeserializeSeed::deserialize(
+            seed,
+            IntoDeserializer::<Error>::into_deserializer(varint as u32),
+        )?;
+        self.deserializer.enter(match self.variants.get(varint) {
+            Some(name) => PathSegment::Variant(name),
+            None => PathSegment::Index(varint),
+        });
+        Ok((v, self.deserializer))
     }
 }
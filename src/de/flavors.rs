@@ -0,0 +1,596 @@
+//! # Deserialization Flavors
+//!
+//! This module contains the deserialization counterpart to the serialization
+//! [`flavors`](../flavors/index.html). "Flavors" on the decoding side are used to supply the
+//! bytes that make up a message to the [`Deserializer`](../struct.Deserializer.html), and may
+//! be combined in the same way as serialization flavors:
+//!
+//! 1. A "source" flavor, such as [`Slice`], which retrieves bytes from some kind of storage.
+//! 2. Modification flavors, which wrap another flavor and process the bytes on their way
+//!    through, for example to verify a checksum or decrypt the data.
+//!
+//! Flavors are implemented using the [`DeFlavor`] trait. When flavors are combined, it is
+//! expected that the source flavor is the innermost flavor.
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use postcard::{de_flavors::Slice, Deserializer};
+//! use serde::Deserialize;
+//!
+//! let data: &[u8] = &[0x01, 0x02, 0x03, 0xAA];
+//! let mut deserializer = Deserializer::from_flavor(Slice::new(data));
+//! let out = <(u8, u16)>::deserialize(&mut deserializer).unwrap();
+//! assert_eq!(out, (0x01, 0x0302));
+//!
+//! let remainder = deserializer.finalize().unwrap();
+//! assert_eq!(remainder, &[0xAA]);
+//! ```
+
+use crate::error::{Error, Result};
+
+#[cfg(feature = "use-std")]
+pub use io_reader::*;
+
+#[cfg(feature = "embedded-io")]
+pub use eio_reader::*;
+
+#[cfg(feature = "bytes")]
+pub use buf_flavor::*;
+
+#[cfg(feature = "use-crc")]
+pub use crc_flavor::*;
+
+/// The DeFlavor trait acts as a combinator/middleware interface that can be used to pass bytes
+/// from a source, through zero or more modification flavors, to the deserializer. See the module
+/// level documentation for more information and examples.
+pub trait DeFlavor<'de>: 'de {
+    /// The `Remainder` type is what this flavor "resolves" to when deserialization is complete.
+    /// For source flavors, this is typically the unused portion of the input. For modification
+    /// flavors, this is typically the remainder of the flavor they are wrapped around.
+    type Remainder: 'de;
+
+    /// Obtain the next byte for deserialization
+    fn pop(&mut self) -> Result<u8>;
+
+    /// Returns the number of bytes remaining in the message, if known.
+    ///
+    /// This is a hint only, and the default implementation returns `None`.
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// Attempt to take the next `ct` bytes from the message. The returned bytes are borrowed
+    /// for the lifetime `'de`, which allows for zero-copy deserialization of types such as
+    /// `&str` or `&[u8]`.
+    fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]>;
+
+    /// Attempt to take the next `ct` bytes from the message, for temporary use only.
+    ///
+    /// This is used for values such as integers, which are immediately copied out of the
+    /// returned slice. Flavors which must copy bytes somewhere in order to provide them for
+    /// `'de`, may override this to avoid using up that space. The default implementation
+    /// calls [`try_take_n()`](DeFlavor::try_take_n).
+    fn try_take_n_temp<'a>(&'a mut self, ct: usize) -> Result<&'a [u8]>
+    where
+        'de: 'a,
+    {
+        self.try_take_n(ct)
+    }
+
+    /// The finalize() trait method completes the deserialization process, and resolves into the
+    /// type defined by the `DeFlavor::Remainder` associated type.
+    fn finalize(self) -> Result<Self::Remainder>;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Source Flavors
+////////////////////////////////////////////////////////////////////////////////
+
+////////////////////////////////////////
+// Slice
+////////////////////////////////////////
+
+/// The `Slice` flavor is a source flavor, reading bytes from a plain `[u8]` slice. Borrowed
+/// types are deserialized in place, without copying. The `Slice` flavor resolves into the
+/// unused portion of the original slice.
+pub struct Slice<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Slice<'de> {
+    /// Create a new `Slice` flavor from a given input buffer
+    pub fn new(input: &'de [u8]) -> Self {
+        Slice { input }
+    }
+}
+
+impl<'de> DeFlavor<'de> for Slice<'de> {
+    type Remainder = &'de [u8];
+
+    #[inline]
+    fn pop(&mut self) -> Result<u8> {
+        let (first, rest) = self
+            .input
+            .split_first()
+            .ok_or(Error::DeserializeUnexpectedEnd)?;
+        self.input = rest;
+        Ok(*first)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.input.len())
+    }
+
+    #[inline]
+    fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+        if self.input.len() >= ct {
+            let (a, b) = self.input.split_at(ct);
+            self.input = b;
+            Ok(a)
+        } else {
+            Err(Error::DeserializeUnexpectedEnd)
+        }
+    }
+
+    fn finalize(self) -> Result<&'de [u8]> {
+        Ok(self.input)
+    }
+}
+
+////////////////////////////////////////
+// Scratch
+////////////////////////////////////////
+
+/// The size of the internal buffer used for temporary reads, large enough for any integer
+#[cfg(any(feature = "use-std", feature = "embedded-io", feature = "bytes"))]
+const TEMP_SIZE: usize = 16;
+
+/// The buffers used by source flavors which have to copy bytes out of their input before
+/// handing them to the deserializer
+#[cfg(any(feature = "use-std", feature = "embedded-io", feature = "bytes"))]
+struct Scratch<'de> {
+    // The unused part of the caller's buffer, for bytes borrowed for `'de`
+    scratch: &'de mut [u8],
+    // Space for bytes which are only needed temporarily
+    temp: [u8; TEMP_SIZE],
+}
+
+#[cfg(any(feature = "use-std", feature = "embedded-io", feature = "bytes"))]
+impl<'de> Scratch<'de> {
+    fn new(scratch: &'de mut [u8]) -> Self {
+        Self {
+            scratch,
+            temp: [0u8; TEMP_SIZE],
+        }
+    }
+
+    /// Use up the next `ct` bytes of the scratch buffer, which the caller fills in
+    fn take(&mut self, ct: usize) -> Result<&'de mut [u8]> {
+        if self.scratch.len() < ct {
+            return Err(Error::DeserializeScratchTooSmall);
+        }
+        let scratch = core::mem::take(&mut self.scratch);
+        let (taken, rest) = scratch.split_at_mut(ct);
+        self.scratch = rest;
+        Ok(taken)
+    }
+
+    /// Get `ct` bytes of space which is only valid until the next call, from the internal
+    /// buffer if they fit, or otherwise from the scratch buffer without using it up
+    fn temp(&mut self, ct: usize) -> Result<&mut [u8]> {
+        if ct <= TEMP_SIZE {
+            Ok(&mut self.temp[..ct])
+        } else if ct <= self.scratch.len() {
+            Ok(&mut self.scratch[..ct])
+        } else {
+            Err(Error::DeserializeScratchTooSmall)
+        }
+    }
+
+    /// The unused part of the scratch buffer
+    fn into_inner(self) -> &'de mut [u8] {
+        self.scratch
+    }
+}
+
+////////////////////////////////////////
+// IoReader
+////////////////////////////////////////
+
+#[cfg(feature = "use-std")]
+mod io_reader {
+    extern crate std;
+    use super::{DeFlavor, Scratch};
+    use crate::error::{Error, Result};
+    use std::io::{self, Read};
+
+    /// The `IoReader` flavor is a source flavor, pulling bytes on demand from a
+    /// [`std::io::Read`] implementation, such as a file or pipe.
+    ///
+    /// Every value is fetched with its own call to `read_exact()`, so readers which are
+    /// expensive to call, such as a `File` or `TcpStream`, should be wrapped in a
+    /// [`std::io::BufReader`]. Strings and byte slices borrowed by the decoded value are
+    /// copied into the scratch buffer, which therefore needs to hold all of them at once,
+    /// while those of a `String` or `Vec<u8>` only pass through it. An end of file in the
+    /// middle of the message is reported as [`Error::DeserializeUnexpectedEnd`], any other
+    /// I/O error as [`Error::DeserializeIoError`].
+    ///
+    /// The `IoReader` flavor resolves into the reader, and the unused portion of the scratch
+    /// buffer. No bytes are read beyond the end of the message, so the reader is left at the
+    /// start of the next one.
+    ///
+    /// This type is only available when the (non-default) `use-std` feature is active
+    ///
+    /// [`Error::DeserializeUnexpectedEnd`]: ../enum.Error.html#variant.DeserializeUnexpectedEnd
+    /// [`Error::DeserializeIoError`]: ../enum.Error.html#variant.DeserializeIoError
+    pub struct IoReader<'de, R: Read> {
+        reader: R,
+        scratch: Scratch<'de>,
+    }
+
+    impl<'de, R: Read> IoReader<'de, R> {
+        /// Create a new `IoReader` flavor from a given reader and scratch buffer
+        pub fn new(reader: R, scratch: &'de mut [u8]) -> Self {
+            Self {
+                reader,
+                scratch: Scratch::new(scratch),
+            }
+        }
+    }
+
+    fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+        reader.read_exact(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => Error::DeserializeUnexpectedEnd,
+            _ => Error::DeserializeIoError,
+        })
+    }
+
+    impl<'de, R: Read + 'de> DeFlavor<'de> for IoReader<'de, R> {
+        type Remainder = (R, &'de mut [u8]);
+
+        #[inline]
+        fn pop(&mut self) -> Result<u8> {
+            let mut byte = [0u8; 1];
+            read_exact(&mut self.reader, &mut byte)?;
+            Ok(byte[0])
+        }
+
+        fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+            let buf = self.scratch.take(ct)?;
+            read_exact(&mut self.reader, buf)?;
+            Ok(buf)
+        }
+
+        fn try_take_n_temp<'a>(&'a mut self, ct: usize) -> Result<&'a [u8]>
+        where
+            'de: 'a,
+        {
+            let buf = self.scratch.temp(ct)?;
+            read_exact(&mut self.reader, buf)?;
+            Ok(buf)
+        }
+
+        fn finalize(self) -> Result<Self::Remainder> {
+            Ok((self.reader, self.scratch.into_inner()))
+        }
+    }
+}
+
+////////////////////////////////////////
+// EioReader
+////////////////////////////////////////
+
+#[cfg(feature = "embedded-io")]
+mod eio_reader {
+    use super::{DeFlavor, Scratch};
+    use crate::error::{Error, Result};
+    use embedded_io::{Read, ReadExactError};
+
+    /// The `EioReader` flavor is a source flavor, pulling bytes on demand from an
+    /// [`embedded_io::Read`] implementation, such as a UART driver, in `no_std` environments.
+    ///
+    /// No allocation is needed: integers are read through a small buffer inside the flavor,
+    /// and the caller provides the scratch buffer, which should be sized for the strings and
+    /// byte slices borrowed by the decoded value. When decoding into a `heapless::String` or
+    /// `heapless::Vec`, it only needs to fit the longest one of them. Running out of input,
+    /// e.g. because the driver reports the end of a transfer, is reported as
+    /// [`Error::DeserializeUnexpectedEnd`], and a driver error as [`Error::DeserializeIoError`].
+    ///
+    /// The `EioReader` flavor resolves into the reader, and the unused portion of the scratch
+    /// buffer. The reader is never asked for bytes beyond the end of the message.
+    ///
+    /// This type is only available when the (non-default) `embedded-io` feature is active
+    ///
+    /// [`Error::DeserializeUnexpectedEnd`]: ../enum.Error.html#variant.DeserializeUnexpectedEnd
+    /// [`Error::DeserializeIoError`]: ../enum.Error.html#variant.DeserializeIoError
+    pub struct EioReader<'de, R: Read> {
+        reader: R,
+        scratch: Scratch<'de>,
+    }
+
+    impl<'de, R: Read> EioReader<'de, R> {
+        /// Create a new `EioReader` flavor from a given reader and scratch buffer
+        pub fn new(reader: R, scratch: &'de mut [u8]) -> Self {
+            Self {
+                reader,
+                scratch: Scratch::new(scratch),
+            }
+        }
+    }
+
+    fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+        reader.read_exact(buf).map_err(|e| match e {
+            ReadExactError::UnexpectedEof => Error::DeserializeUnexpectedEnd,
+            ReadExactError::Other(_) => Error::DeserializeIoError,
+        })
+    }
+
+    impl<'de, R: Read + 'de> DeFlavor<'de> for EioReader<'de, R> {
+        type Remainder = (R, &'de mut [u8]);
+
+        #[inline]
+        fn pop(&mut self) -> Result<u8> {
+            let mut byte = [0u8; 1];
+            read_exact(&mut self.reader, &mut byte)?;
+            Ok(byte[0])
+        }
+
+        fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+            let buf = self.scratch.take(ct)?;
+            read_exact(&mut self.reader, buf)?;
+            Ok(buf)
+        }
+
+        fn try_take_n_temp<'a>(&'a mut self, ct: usize) -> Result<&'a [u8]>
+        where
+            'de: 'a,
+        {
+            let buf = self.scratch.temp(ct)?;
+            read_exact(&mut self.reader, buf)?;
+            Ok(buf)
+        }
+
+        fn finalize(self) -> Result<Self::Remainder> {
+            Ok((self.reader, self.scratch.into_inner()))
+        }
+    }
+}
+
+////////////////////////////////////////
+// BufFlavor
+////////////////////////////////////////
+
+#[cfg(feature = "bytes")]
+mod buf_flavor {
+    use super::{DeFlavor, Scratch};
+    use crate::error::{Error, Result};
+    use core::ops::Deref;
+
+    /// The `BufFlavor` is a source flavor, reading bytes from a list of non-contiguous chunks,
+    /// such as the `Bytes` making up a [`bytes::Buf`], or the `IoSlice`s filled in by
+    /// [`Buf::chunks_vectored()`](bytes::Buf::chunks_vectored).
+    ///
+    /// Borrowed types such as `&str` or `&[u8]` are deserialized in place, without copying,
+    /// when they are contained in a single chunk. Only when they span more than one chunk are
+    /// they copied into the given scratch buffer. Bytes which are only needed temporarily, such
+    /// as those of integers, or of owned types such as `String` or `Vec<u8>`, are read in place
+    /// as well, and are only copied when they span more than one chunk, without using up the
+    /// scratch buffer.
+    ///
+    /// The `BufFlavor` resolves into the number of bytes read, which the `Buf` may be advanced
+    /// by afterwards, and the unused portion of the scratch buffer.
+    ///
+    /// This type is only available when the (non-default) `bytes` feature is active
+    pub struct BufFlavor<'de, C> {
+        chunks: Chunks<'de, C>,
+        scratch: Scratch<'de>,
+    }
+
+    /// The unread part of the chunks of a [`BufFlavor`]
+    struct Chunks<'de, C> {
+        // The unread part of the current chunk
+        chunk: &'de [u8],
+        // The chunks following the current one
+        rest: &'de [C],
+        // Number of bytes in all chunks
+        total: usize,
+        // Number of unread bytes in all chunks
+        remaining: usize,
+    }
+
+    impl<'de, C: Deref<Target = [u8]>> BufFlavor<'de, C> {
+        /// Create a new `BufFlavor` from the given chunks and scratch buffer
+        pub fn new(chunks: &'de [C], scratch: &'de mut [u8]) -> Self {
+            let total = chunks.iter().map(|chunk| chunk.len()).sum();
+            Self {
+                chunks: Chunks {
+                    chunk: &[],
+                    rest: chunks,
+                    total,
+                    remaining: total,
+                },
+                scratch: Scratch::new(scratch),
+            }
+        }
+    }
+
+    impl<'de, C: Deref<Target = [u8]>> Chunks<'de, C> {
+        /// Check that `ct` bytes are left, and move on to the next non-empty chunk, if the
+        /// current one has been read
+        fn prepare(&mut self, ct: usize) -> Result<()> {
+            if self.remaining < ct {
+                return Err(Error::DeserializeUnexpectedEnd);
+            }
+            while self.chunk.is_empty() {
+                match self.rest.split_first() {
+                    Some((first, rest)) => {
+                        self.chunk = first;
+                        self.rest = rest;
+                    }
+                    None => break,
+                }
+            }
+            Ok(())
+        }
+
+        /// Take the next `ct` bytes, if they are contained in the current chunk
+        fn take_in_place(&mut self, ct: usize) -> Option<&'de [u8]> {
+            if self.chunk.len() < ct {
+                return None;
+            }
+            let (taken, rest) = self.chunk.split_at(ct);
+            self.chunk = rest;
+            self.remaining -= ct;
+            Some(taken)
+        }
+
+        /// Copy the next bytes into `out`, which must not be longer than the remaining bytes
+        fn copy_to(&mut self, out: &mut [u8]) {
+            let mut filled = 0;
+            while filled < out.len() {
+                // Only fails if there are not enough bytes left, which has been checked
+                let _ = self.prepare(0);
+                let n = self.chunk.len().min(out.len() - filled);
+                out[filled..][..n].copy_from_slice(&self.chunk[..n]);
+                self.chunk = &self.chunk[n..];
+                filled += n;
+            }
+            self.remaining -= out.len();
+        }
+    }
+
+    impl<'de, C: Deref<Target = [u8]> + 'de> DeFlavor<'de> for BufFlavor<'de, C> {
+        type Remainder = (usize, &'de mut [u8]);
+
+        #[inline]
+        fn pop(&mut self) -> Result<u8> {
+            self.chunks.prepare(1)?;
+            let byte = self.chunks.chunk[0];
+            self.chunks.chunk = &self.chunks.chunk[1..];
+            self.chunks.remaining -= 1;
+            Ok(byte)
+        }
+
+        fn size_hint(&self) -> Option<usize> {
+            Some(self.chunks.remaining)
+        }
+
+        fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+            self.chunks.prepare(ct)?;
+            if let Some(taken) = self.chunks.take_in_place(ct) {
+                return Ok(taken);
+            }
+
+            // The bytes span more than one chunk, and are copied into the scratch buffer
+            let taken = self.scratch.take(ct)?;
+            self.chunks.copy_to(taken);
+            Ok(taken)
+        }
+
+        fn try_take_n_temp<'a>(&'a mut self, ct: usize) -> Result<&'a [u8]>
+        where
+            'de: 'a,
+        {
+            self.chunks.prepare(ct)?;
+            if let Some(taken) = self.chunks.take_in_place(ct) {
+                return Ok(taken);
+            }
+
+            let buf = self.scratch.temp(ct)?;
+            self.chunks.copy_to(buf);
+            Ok(buf)
+        }
+
+        fn finalize(self) -> Result<Self::Remainder> {
+            Ok((
+                self.chunks.total - self.chunks.remaining,
+                self.scratch.into_inner(),
+            ))
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Modification Flavors
+////////////////////////////////////////////////////////////////////////////////
+
+////////////////////////////////////////
+// CRC
+////////////////////////////////////////
+
+#[cfg(feature = "use-crc")]
+mod crc_flavor {
+    use super::DeFlavor;
+    use crate::checksum::CrcDigest;
+    use crate::error::{Error, Result};
+
+    /// The `Crc` flavor calculates a [Cyclic Redundancy Check] over all deserialized bytes.
+    /// When finalized, it takes the checksum appended to the message by the serialization
+    /// [`Crc`](../flavors/struct.Crc.html) flavor, and verifies it.
+    ///
+    /// This type is only available when the (non-default) `use-crc` feature is active.
+    ///
+    /// [Cyclic Redundancy Check]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+    pub struct Crc<F, D> {
+        flav: F,
+        digest: D,
+    }
+
+    impl<F, D> Crc<F, D>
+    where
+        D: CrcDigest,
+    {
+        /// Create a new Crc modifier Flavor, using the given digest
+        pub fn new(flav: F, digest: D) -> Self {
+            Self { flav, digest }
+        }
+    }
+
+    impl<'de, F, D> DeFlavor<'de> for Crc<F, D>
+    where
+        F: DeFlavor<'de>,
+        D: CrcDigest + 'de,
+    {
+        type Remainder = F::Remainder;
+
+        #[inline]
+        fn pop(&mut self) -> Result<u8> {
+            let byte = self.flav.pop()?;
+            self.digest.update(&[byte]);
+            Ok(byte)
+        }
+
+        #[inline]
+        fn size_hint(&self) -> Option<usize> {
+            self.flav.size_hint()?.checked_sub(D::SIZE)
+        }
+
+        #[inline]
+        fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+            let bytes = self.flav.try_take_n(ct)?;
+            self.digest.update(bytes);
+            Ok(bytes)
+        }
+
+        #[inline]
+        fn try_take_n_temp<'a>(&'a mut self, ct: usize) -> Result<&'a [u8]>
+        where
+            'de: 'a,
+        {
+            let bytes = self.flav.try_take_n_temp(ct)?;
+            self.digest.update(bytes);
+            Ok(bytes)
+        }
+
+        fn finalize(mut self) -> Result<Self::Remainder> {
+            let expected = self.digest.finalize().to_le_bytes();
+            let found = self.flav.try_take_n_temp(D::SIZE)?;
+            if found != &expected[..D::SIZE] {
+                return Err(Error::DeserializeBadCrc);
+            }
+            self.flav.finalize()
+        }
+    }
+}
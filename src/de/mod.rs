@@ -2,8 +2,14 @@ use cobs::decode_in_place;
 use serde::Deserialize;
 
 pub(crate) mod deserializer;
-
-use crate::error::{Error, Result};
+pub mod flavors;
+
+#[cfg(feature = "use-crc")]
+use crate::checksum::CrcDigest;
+use crate::error::{DetailedError, Error, Result};
+use crate::hdlc;
+use crate::ser::flavors::PrefixFormat;
+use crate::slip;
 use deserializer::Deserializer;
 
 /// Deserialize a message of type `T` from a byte slice. The unused portion (if any)
@@ -17,6 +23,72 @@ where
     Ok(t)
 }
 
+/// Deserialize a message of type `T` from a byte slice, returning a [`DetailedError`]
+/// on failure. The unused portion (if any) of the byte slice is not returned.
+///
+/// This works like [`from_bytes()`](fn.from_bytes.html), but additionally reports the
+/// offset into the input at which deserialization failed, and with the `alloc` feature,
+/// the path to the value that was being decoded.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::{from_bytes_detailed, Error};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// struct Packet {
+///     id: u8,
+///     flag: bool,
+/// }
+///
+/// let err = from_bytes_detailed::<Packet>(&[0x01, 0x02]).unwrap_err();
+/// assert_eq!(err.kind(), &Error::DeserializeBadBool);
+/// assert_eq!(err.offset(), 2);
+/// ```
+///
+/// [`DetailedError`]: struct.DetailedError.html
+pub fn from_bytes_detailed<'a, T>(s: &'a [u8]) -> core::result::Result<T, DetailedError>
+where
+    T: Deserialize<'a>,
+{
+    Ok(take_from_bytes_detailed(s)?.0)
+}
+
+/// Deserialize a message of type `T` from a byte slice, returning a [`DetailedError`]
+/// on failure. The unused portion (if any) of the byte slice is returned for further usage.
+///
+/// See [`from_bytes_detailed()`](fn.from_bytes_detailed.html) for details.
+///
+/// [`DetailedError`]: struct.DetailedError.html
+pub fn take_from_bytes_detailed<'a, T>(
+    s: &'a [u8],
+) -> core::result::Result<(T, &'a [u8]), DetailedError>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_bytes(s);
+    #[cfg(feature = "alloc")]
+    deserializer.record_path();
+    let result = T::deserialize(&mut deserializer);
+    deserializer.finalize_detailed(s, result)
+}
+
+/// Deserialize a message of type `T` from a byte slice, where all integers wider
+/// than a byte are encoded as varints. The unused portion (if any) of the byte slice
+/// is not returned.
+///
+/// This is the counterpart to [`to_slice_varint()`](fn.to_slice_varint.html) and
+/// [`serialize_with_flavor_varint()`](fn.serialize_with_flavor_varint.html).
+pub fn from_bytes_varint<'a, T>(s: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_bytes_varint(s);
+    let t = T::deserialize(&mut deserializer)?;
+    Ok(t)
+}
+
 /// Deserialize a message of type `T` from a cobs-encoded byte slice. The
 /// unused portion (if any) of the byte slice is not returned.
 pub fn from_bytes_cobs<'a, T>(s: &'a mut [u8]) -> Result<T>
@@ -38,6 +110,123 @@ where
     Ok((from_bytes::<T>(used)?, unused))
 }
 
+/// Deserialize a message of type `T` from a SLIP-encoded byte slice. The data is
+/// decoded in place, and any leading `END` bytes are skipped. The unused portion
+/// (if any) of the byte slice is not returned.
+pub fn from_bytes_slip<'a, T>(s: &'a mut [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let (frame, _) = slip::decode_in_place(s)?;
+    from_bytes::<T>(&s[frame])
+}
+
+/// Deserialize a message of type `T` from a SLIP-encoded byte slice. The data is
+/// decoded in place, and any leading `END` bytes are skipped. The portion of the
+/// byte slice following the terminating `END` byte (if any) is returned for further usage.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::take_from_bytes_slip;
+///
+/// let data: &mut [u8] = &mut [0xC0, 0xDB, 0xDC, 0xC0, 0x01, 0xC0];
+/// let (out, rest) = take_from_bytes_slip::<u8>(data).unwrap();
+/// assert_eq!(out, 0xC0);
+/// assert_eq!(rest, &[0x01, 0xC0]);
+/// ```
+pub fn take_from_bytes_slip<'a, T>(s: &'a mut [u8]) -> Result<(T, &'a mut [u8])>
+where
+    T: Deserialize<'a>,
+{
+    let (frame, consumed) = slip::decode_in_place(s)?;
+    let (used, unused) = s.split_at_mut(consumed);
+    Ok((from_bytes::<T>(&used[frame])?, unused))
+}
+
+/// Deserialize a message of type `T` from an HDLC framed byte slice, as produced by the
+/// [`Hdlc`] flavor. The data is decoded in place, any leading flag bytes are skipped, and
+/// the frame check sequence is verified. The unused portion (if any) of the byte slice is
+/// not returned.
+///
+/// [`Hdlc`]: flavors/struct.Hdlc.html
+pub fn from_bytes_hdlc<'a, T>(s: &'a mut [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let (frame, _) = hdlc::decode_in_place(s)?;
+    from_bytes::<T>(&s[frame])
+}
+
+/// Deserialize a message of type `T` from an HDLC framed byte slice, as produced by the
+/// [`Hdlc`] flavor. The data is decoded in place, any leading flag bytes are skipped, and
+/// the frame check sequence is verified. The portion of the byte slice following the
+/// closing flag byte (if any) is returned for further usage.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::take_from_bytes_hdlc;
+///
+/// let data: &mut [u8] = &mut [0x7E, 0x7D, 0x5E, 0x81, 0x6A, 0x7E, 0x7E];
+/// let (out, rest) = take_from_bytes_hdlc::<u8>(data).unwrap();
+/// assert_eq!(out, 0x7E);
+/// assert_eq!(rest, &[0x7E]);
+/// ```
+///
+/// [`Hdlc`]: flavors/struct.Hdlc.html
+pub fn take_from_bytes_hdlc<'a, T>(s: &'a mut [u8]) -> Result<(T, &'a mut [u8])>
+where
+    T: Deserialize<'a>,
+{
+    let (frame, consumed) = hdlc::decode_in_place(s)?;
+    let (used, unused) = s.split_at_mut(consumed);
+    Ok((from_bytes::<T>(&used[frame])?, unused))
+}
+
+/// Deserialize a message of type `T` from a byte slice, which is preceded by its length
+/// in the given [`PrefixFormat`]. The unused portion (if any) of the byte slice is not
+/// returned.
+///
+/// [`PrefixFormat`]: flavors/enum.PrefixFormat.html
+pub fn from_bytes_length_prefix<'a, T>(s: &'a [u8], format: PrefixFormat) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    Ok(take_from_bytes_length_prefix(s, format)?.0)
+}
+
+/// Deserialize a message of type `T` from a byte slice, which is preceded by its length
+/// in the given [`PrefixFormat`]. The unused portion (if any) of the byte slice following
+/// the message is returned for further usage.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::{flavors::PrefixFormat, take_from_bytes_length_prefix};
+///
+/// let data: &[u8] = &[0x02, 0x00, 0x01, 0x01, 0x03, 0x00];
+/// let (out, rest) = take_from_bytes_length_prefix::<u8>(data, PrefixFormat::U16).unwrap();
+/// assert_eq!(out, 0x01);
+/// assert_eq!(rest, &[0x03, 0x00]);
+/// ```
+///
+/// [`PrefixFormat`]: flavors/enum.PrefixFormat.html
+pub fn take_from_bytes_length_prefix<'a, T>(
+    s: &'a [u8],
+    format: PrefixFormat,
+) -> Result<(T, &'a [u8])>
+where
+    T: Deserialize<'a>,
+{
+    let (hdr, len) = format.decode(s)?.ok_or(Error::DeserializeUnexpectedEnd)?;
+    let frame = s
+        .get(hdr..)
+        .and_then(|rest| rest.get(..len))
+        .ok_or(Error::DeserializeUnexpectedEnd)?;
+    Ok((from_bytes(frame)?, &s[hdr + len..]))
+}
+
 /// Deserialize a message of type `T` from a byte slice. The unused portion (if any)
 /// of the byte slice is returned for further usage
 pub fn take_from_bytes<'a, T>(s: &'a [u8]) -> Result<(T, &'a [u8])>
@@ -46,7 +235,364 @@ where
 {
     let mut deserializer = Deserializer::from_bytes(s);
     let t = T::deserialize(&mut deserializer)?;
-    Ok((t, deserializer.input))
+    Ok((t, deserializer.finalize()?))
+}
+
+/// Deserialize a message of type `T` from a byte slice, where all integers wider
+/// than a byte are encoded as varints. The unused portion (if any) of the byte slice
+/// is returned for further usage
+pub fn take_from_bytes_varint<'a, T>(s: &'a [u8]) -> Result<(T, &'a [u8])>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_bytes_varint(s);
+    let t = T::deserialize(&mut deserializer)?;
+    Ok((t, deserializer.finalize()?))
+}
+
+/// Deserialize a message of type `T` directly from a [`std::io::Read`] implementation,
+/// such as a file or a pipe. Bytes are pulled from the reader as they are needed, and no
+/// bytes following the message are consumed. The reader is returned for further usage.
+/// Requires the `use-std` feature.
+///
+/// Borrowed data, such as `&str` or `&[u8]`, is copied into the `scratch` buffer. Owned
+/// strings and byte buffers use it temporarily. If the `scratch` buffer is too small,
+/// [`Error::DeserializeScratchTooSmall`] is returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::from_io;
+///
+/// let data: &[u8] = &[0x01, 0x03, b'H', b'i', b'!', 0x05, b't', b'h', b'e', b'r', b'e'];
+/// let mut scratch = [0u8; 8];
+///
+/// let (out, rest) = from_io::<(bool, &str), _>(data, &mut scratch).unwrap();
+/// assert_eq!(out, (true, "Hi!"));
+///
+/// let (out, _) = from_io::<String, _>(rest, &mut [0u8; 8]).unwrap();
+/// assert_eq!(out, "there");
+/// ```
+///
+/// [`Error::DeserializeScratchTooSmall`]: enum.Error.html#variant.DeserializeScratchTooSmall
+#[cfg(feature = "use-std")]
+pub fn from_io<'a, T, R>(reader: R, scratch: &'a mut [u8]) -> Result<(T, R)>
+where
+    T: Deserialize<'a>,
+    R: std::io::Read + 'a,
+{
+    let mut deserializer = Deserializer::from_flavor(flavors::IoReader::new(reader, scratch));
+    let t = T::deserialize(&mut deserializer)?;
+    let (reader, _scratch) = deserializer.finalize()?;
+    Ok((t, reader))
+}
+
+/// Deserialize a message of type `T` from an [`embedded_io::Read`] implementation, such as
+/// a UART driver, without reading the whole message into memory first. Returns the reader,
+/// which has not been read beyond the end of the message. Requires the `embedded-io` feature.
+///
+/// This works like [`from_io()`](fn.from_io.html), and is available in `no_std`
+/// environments. Borrowed data, such as `&str` or `&[u8]`, is copied into the `scratch`
+/// buffer, while owned data only uses it temporarily. If the `scratch` buffer is too small,
+/// [`Error::DeserializeScratchTooSmall`] is returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::from_eio;
+///
+/// let data: &[u8] = &[0x01, 0x03, b'H', b'i', b'!', 0x05];
+/// let mut scratch = [0u8; 8];
+///
+/// let (out, rest) = from_eio::<(bool, &str), _>(data, &mut scratch).unwrap();
+/// assert_eq!(out, (true, "Hi!"));
+/// assert_eq!(rest, &[0x05]);
+/// ```
+///
+/// [`Error::DeserializeScratchTooSmall`]: enum.Error.html#variant.DeserializeScratchTooSmall
+#[cfg(feature = "embedded-io")]
+pub fn from_eio<'a, T, R>(reader: R, scratch: &'a mut [u8]) -> Result<(T, R)>
+where
+    T: Deserialize<'a>,
+    R: embedded_io::Read + 'a,
+{
+    let mut deserializer = Deserializer::from_flavor(flavors::EioReader::new(reader, scratch));
+    let t = T::deserialize(&mut deserializer)?;
+    let (reader, _scratch) = deserializer.finalize()?;
+    Ok((t, reader))
+}
+
+/// Deserialize a message of type `T` from a [`bytes::Buf`] implementation, which may be made
+/// up of several non-contiguous chunks, such as a `Chain` of `Bytes`. The `Buf` is advanced
+/// to the end of the message. Requires the `bytes` and `use-std` features.
+///
+/// The chunks are gathered with [`Buf::chunks_vectored()`](bytes::Buf::chunks_vectored), so
+/// only the chunks it returns are read. Most implementations in the `bytes` crate return all of
+/// their chunks, but the default implementation only returns the first one. Owned data is read
+/// in place, and only copied if it spans more than one chunk, using the `scratch` buffer for
+/// data which does not fit into a small internal buffer. If the `scratch` buffer is too small,
+/// [`Error::DeserializeScratchTooSmall`] is returned. To deserialize borrowed data, use
+/// [`from_buf_chunks()`].
+///
+/// ## Example
+///
+/// ```rust
+/// use bytes::{Buf, Bytes};
+/// use postcard::from_buf;
+///
+/// let mut data = Bytes::from_static(&[0x01, 0x03, b'H'])
+///     .chain(Bytes::from_static(&[b'i', b'!', 0x05]));
+/// let mut scratch = [0u8; 8];
+///
+/// let out: (bool, String) = from_buf(&mut data, &mut scratch).unwrap();
+/// assert_eq!(out, (true, "Hi!".to_string()));
+/// assert_eq!(data.chunk(), &[0x05]);
+/// ```
+///
+/// [`Error::DeserializeScratchTooSmall`]: enum.Error.html#variant.DeserializeScratchTooSmall
+#[cfg(all(feature = "bytes", feature = "use-std"))]
+pub fn from_buf<T, B>(buf: &mut B, scratch: &mut [u8]) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    B: bytes::Buf,
+{
+    use std::io::IoSlice;
+
+    let mut chunks = vec![IoSlice::new(&[]); 16];
+    loop {
+        let filled = buf.chunks_vectored(&mut chunks);
+        if filled < chunks.len() {
+            chunks.truncate(filled);
+            break;
+        }
+        chunks.resize(chunks.len() * 2, IoSlice::new(&[]));
+    }
+
+    let (t, used) = from_buf_chunks(&chunks, scratch)?;
+    drop(chunks);
+    buf.advance(used);
+    Ok(t)
+}
+
+/// Deserialize a message of type `T` from a list of non-contiguous chunks, such as the `Bytes`
+/// making up a [`bytes::Buf`]. Returns the number of bytes read, which the `Buf` may be
+/// advanced by afterwards. Requires the `bytes` feature.
+///
+/// This is the lower-level counterpart to [`from_buf()`], which also allows deserializing
+/// borrowed data. Borrowed data, such as `&str` or `&[u8]`, is deserialized in place when it is
+/// contained in a single chunk, and only copied into the `scratch` buffer if it spans more than
+/// one chunk. If the `scratch` buffer is too small, [`Error::DeserializeScratchTooSmall`] is
+/// returned. See the [`BufFlavor`](flavors::BufFlavor) for details.
+///
+/// ## Example
+///
+/// ```rust
+/// use bytes::Bytes;
+/// use postcard::from_buf_chunks;
+///
+/// let chunks = [
+///     Bytes::from_static(&[0x01, 0x03, b'H']),
+///     Bytes::from_static(&[b'i', b'!', 0x03, b'B', b'y', b'e', 0x05]),
+/// ];
+/// let mut scratch = [0u8; 8];
+///
+/// let (out, used) = from_buf_chunks::<(bool, &str, &str), _>(&chunks, &mut scratch).unwrap();
+/// assert_eq!(out, (true, "Hi!", "Bye"));
+/// assert_eq!(used, 9);
+///
+/// // "Bye" is borrowed from the second chunk, while "Hi!" had to be copied
+/// assert!(chunks[1].as_ptr_range().contains(&out.2.as_ptr()));
+/// assert_eq!(&scratch[..3], b"Hi!");
+/// ```
+///
+/// [`Error::DeserializeScratchTooSmall`]: enum.Error.html#variant.DeserializeScratchTooSmall
+#[cfg(feature = "bytes")]
+pub fn from_buf_chunks<'a, T, C>(chunks: &'a [C], scratch: &'a mut [u8]) -> Result<(T, usize)>
+where
+    T: Deserialize<'a>,
+    C: core::ops::Deref<Target = [u8]>,
+{
+    let mut deserializer = Deserializer::from_flavor(flavors::BufFlavor::new(chunks, scratch));
+    let t = T::deserialize(&mut deserializer)?;
+    let (used, _scratch) = deserializer.finalize()?;
+    Ok((t, used))
+}
+
+/// Deserialize a message of type `T` from an [`embedded_io_async::BufRead`] implementation,
+/// such as an Embassy buffered UART driver. Returns the reader, which has not been read beyond
+/// the end of the message. Requires the `embedded-io-async` feature.
+///
+/// The input is copied into `buf` until it holds a complete message, as deserialization itself
+/// can not wait for the reader. If the message does not fit into `buf`,
+/// [`Error::DeserializeBufferFull`] is returned. If the reader reaches its end before the
+/// message is complete, [`Error::DeserializeUnexpectedEnd`] is returned, and if it returns an
+/// error, [`Error::DeserializeIoError`].
+///
+/// Each time the reader returns more data, the message is deserialized again from the start of
+/// `buf`, so the time taken grows quadratically with the number of reads a message needs. For
+/// large messages arriving in small pieces, frame them with COBS and receive them with
+/// [`Accumulator::read_frame()`](crate::Accumulator::read_frame), which decodes the input
+/// as it arrives.
+///
+/// ## Example
+///
+/// ```rust
+/// use embassy_futures::block_on;
+/// use postcard::from_async_reader;
+///
+/// let data: &[u8] = &[0x01, 0x34, 0x12, 0x05];
+/// let mut buf = [0u8; 8];
+///
+/// let (out, rest) = block_on(from_async_reader::<(bool, u16), _>(data, &mut buf)).unwrap();
+/// assert_eq!(out, (true, 0x1234));
+/// assert_eq!(rest, &[0x05]);
+/// ```
+///
+/// [`Error::DeserializeBufferFull`]: enum.Error.html#variant.DeserializeBufferFull
+/// [`Error::DeserializeUnexpectedEnd`]: enum.Error.html#variant.DeserializeUnexpectedEnd
+/// [`Error::DeserializeIoError`]: enum.Error.html#variant.DeserializeIoError
+#[cfg(feature = "embedded-io-async")]
+pub async fn from_async_reader<T, R>(mut reader: R, buf: &mut [u8]) -> Result<(T, R)>
+where
+    T: serde::de::DeserializeOwned,
+    R: embedded_io_async::BufRead,
+{
+    let mut used = 0;
+    loop {
+        let input = reader
+            .fill_buf()
+            .await
+            .map_err(|_| Error::DeserializeIoError)?;
+        let (t, len) = read_step(input, buf, &mut used)?;
+        reader.consume(len);
+        if let Some(t) = t {
+            return Ok((t, reader));
+        }
+    }
+}
+
+/// Deserialize a message of type `T` from a [`futures_io::AsyncBufRead`] implementation.
+/// Returns the reader, which has not been read beyond the end of the message. Requires the
+/// `futures-io` feature.
+///
+/// This is the counterpart to [`from_async_reader()`], see its documentation for the
+/// requirements on `buf`, and the errors returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use postcard::from_futures_reader;
+///
+/// let data: &[u8] = &[0x01, 0x34, 0x12, 0x05];
+/// let mut buf = [0u8; 8];
+///
+/// let (out, rest) = block_on(from_futures_reader::<(bool, u16), _>(data, &mut buf)).unwrap();
+/// assert_eq!(out, (true, 0x1234));
+/// assert_eq!(rest, &[0x05]);
+/// ```
+#[cfg(feature = "futures-io")]
+pub async fn from_futures_reader<T, R>(mut reader: R, buf: &mut [u8]) -> Result<(T, R)>
+where
+    T: serde::de::DeserializeOwned,
+    R: futures_io::AsyncBufRead + Unpin,
+{
+    use core::pin::Pin;
+
+    let mut used = 0;
+    loop {
+        let (t, len) = core::future::poll_fn(|cx| {
+            Pin::new(&mut reader).poll_fill_buf(cx).map(|input| {
+                let input = input.map_err(|_| Error::DeserializeIoError)?;
+                read_step::<T>(input, buf, &mut used)
+            })
+        })
+        .await?;
+        Pin::new(&mut reader).consume(len);
+        if let Some(t) = t {
+            return Ok((t, reader));
+        }
+    }
+}
+
+/// Append `input` to the `used` bytes of `buf`, and try to deserialize a `T` from them. Returns
+/// the message, if it is complete, and the number of input bytes to consume, which ends at the
+/// end of the message.
+#[cfg(any(feature = "embedded-io-async", feature = "futures-io"))]
+fn read_step<T>(input: &[u8], buf: &mut [u8], used: &mut usize) -> Result<(Option<T>, usize)>
+where
+    T: serde::de::DeserializeOwned,
+{
+    if input.is_empty() {
+        return Err(Error::DeserializeUnexpectedEnd);
+    }
+    let len = input.len().min(buf.len() - *used);
+    if len == 0 {
+        return Err(Error::DeserializeBufferFull);
+    }
+    buf[*used..][..len].copy_from_slice(&input[..len]);
+
+    // Only consume the input up to the end of the message
+    match take_from_bytes::<T>(&buf[..*used + len]) {
+        Ok((t, rest)) => Ok((Some(t), len - rest.len())),
+        Err(Error::DeserializeUnexpectedEnd) => {
+            *used += len;
+            Ok((None, len))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Deserialize a message of type `T` from a byte slice, verifying the checksum that was
+/// appended to the message by the [`Crc`](flavors/struct.Crc.html) serialization flavor. The
+/// unused portion (if any) of the byte slice following the checksum is not returned.
+///
+/// Requires the (non-default) `use-crc` feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use crc::{Crc, CRC_32_ISCSI};
+/// use postcard::{from_bytes_crc, to_slice_crc, Error};
+///
+/// const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+///
+/// let mut buf = [0u8; 32];
+/// let used = to_slice_crc(&0x1234u16, &mut buf, CRC.digest()).unwrap();
+/// assert_eq!(used, &[0x34, 0x12, 0x15, 0xB3, 0xC0, 0x0E]);
+///
+/// let out: u16 = from_bytes_crc(used, CRC.digest()).unwrap();
+/// assert_eq!(out, 0x1234);
+///
+/// used[0] = 0x35;
+/// let res = from_bytes_crc::<u16, _>(used, CRC.digest());
+/// assert_eq!(res, Err(Error::DeserializeBadCrc));
+/// ```
+#[cfg(feature = "use-crc")]
+pub fn from_bytes_crc<'a, T, D>(s: &'a [u8], digest: D) -> Result<T>
+where
+    T: Deserialize<'a>,
+    D: CrcDigest + 'a,
+{
+    Ok(take_from_bytes_crc(s, digest)?.0)
+}
+
+/// Deserialize a message of type `T` from a byte slice, verifying the checksum that was
+/// appended to the message by the [`Crc`](flavors/struct.Crc.html) serialization flavor. The
+/// unused portion (if any) of the byte slice following the checksum is returned for further
+/// usage.
+///
+/// Requires the (non-default) `use-crc` feature.
+#[cfg(feature = "use-crc")]
+pub fn take_from_bytes_crc<'a, T, D>(s: &'a [u8], digest: D) -> Result<(T, &'a [u8])>
+where
+    T: Deserialize<'a>,
+    D: CrcDigest + 'a,
+{
+    let flavor = flavors::Crc::new(flavors::Slice::new(s), digest);
+    let mut deserializer = Deserializer::from_flavor(flavor);
+    let t = T::deserialize(&mut deserializer)?;
+    Ok((t, deserializer.finalize()?))
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -464,4 +1010,103 @@ mod test_heapless {
 
         assert_eq!(input, out);
     }
+
+    fn varint_loopback<T>(data: T)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + core::fmt::Debug,
+    {
+        let mut buf = [0u8; 64];
+        let used = crate::to_slice_varint(&data, &mut buf).unwrap();
+        let (out, rest): (T, _) = take_from_bytes_varint(used).unwrap();
+        assert_eq!(out, data);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn de_varint() {
+        varint_loopback(0u16);
+        varint_loopback(u16::MAX);
+        varint_loopback(u32::MAX);
+        varint_loopback(u64::MAX);
+        varint_loopback(u128::MAX);
+        varint_loopback(i16::MIN);
+        varint_loopback(i16::MAX);
+        varint_loopback(i32::MIN);
+        varint_loopback(i32::MAX);
+        varint_loopback(i64::MIN);
+        varint_loopback(i64::MAX);
+        varint_loopback(i128::MIN);
+        varint_loopback(i128::MAX);
+        varint_loopback(-1i64);
+        varint_loopback(BasicU8S {
+            st: 0xABCD,
+            ei: 0xFE,
+            ote: 0x1234_4321_ABCD_DCBA_1234_4321_ABCD_DCBA,
+            sf: 0x1234_4321_ABCD_DCBA,
+            tt: 0xACAC_ACAC,
+        });
+        varint_loopback(DataEnum::Chi { a: 0x0F, b: 5 });
+
+        // Too many bytes for a u16
+        assert_eq!(
+            from_bytes_varint::<u16>(&[0xFF, 0xFF, 0xFF, 0x01]),
+            Err(Error::DeserializeBadVarint)
+        );
+
+        // Correct length, but overflows a u16
+        assert_eq!(
+            from_bytes_varint::<u16>(&[0xFF, 0xFF, 0x04]),
+            Err(Error::DeserializeBadVarint)
+        );
+
+        assert_eq!(
+            from_bytes_varint::<u32>(&[0x80, 0x80]),
+            Err(Error::DeserializeUnexpectedEnd)
+        );
+    }
+
+    /// A modification flavor that counts the bytes passing through it
+    struct Counting<F> {
+        inner: F,
+        count: usize,
+    }
+
+    impl<'de, F: flavors::DeFlavor<'de>> flavors::DeFlavor<'de> for Counting<F> {
+        type Remainder = (F::Remainder, usize);
+
+        fn pop(&mut self) -> Result<u8> {
+            self.count += 1;
+            self.inner.pop()
+        }
+
+        fn try_take_n(&mut self, ct: usize) -> Result<&'de [u8]> {
+            self.count += ct;
+            self.inner.try_take_n(ct)
+        }
+
+        fn finalize(self) -> Result<Self::Remainder> {
+            Ok((self.inner.finalize()?, self.count))
+        }
+    }
+
+    #[test]
+    fn de_flavor_layering() {
+        let input = RefStruct {
+            bytes: &[0x01, 0x02],
+            str_s: "hi",
+        };
+        let mut output: Vec<u8, 16> = to_vec(&input).unwrap();
+        output.extend_from_slice(&[0xAA, 0xBB]).unwrap();
+
+        let mut de = deserializer::Deserializer::from_flavor(Counting {
+            inner: flavors::Slice::new(output.deref()),
+            count: 0,
+        });
+        let out = RefStruct::deserialize(&mut de).unwrap();
+        assert_eq!(out, input);
+
+        let (rest, count) = de.finalize().unwrap();
+        assert_eq!(rest, &[0xAA, 0xBB]);
+        assert_eq!(count, 6);
+    }
 }
@@ -0,0 +1,466 @@
+//! # Dynamic (schema driven) encoding and decoding
+//!
+//! This module allows decoding postcard messages into a generic [`Value`] tree, and encoding
+//! them back, using only a runtime description of the message layout: an [`OwnedNamedType`].
+//! This is useful for tools that need to work with messages whose Rust types they do not have
+//! access to, for example when displaying telemetry sent by a different firmware version.
+//!
+//! The schema is typically obtained from the [`Schema`](../trait.Schema.html) trait on the
+//! sending side, and transferred to the tool, e.g. as a postcard message itself.
+//!
+//! This module is only available when the (non-default) `alloc` feature is active.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use postcard::{
+//!     dynamic::{self, Value},
+//!     schema::OwnedNamedType,
+//!     Schema,
+//! };
+//!
+//! // On the "device" side
+//! let schema_bytes = postcard::to_allocvec(<(u8, Option<i16>)>::SCHEMA).unwrap();
+//! let message = postcard::to_allocvec(&(4u8, Some(-2i16))).unwrap();
+//!
+//! // On the "host" side
+//! let schema: OwnedNamedType = postcard::from_bytes(&schema_bytes).unwrap();
+//! let value = dynamic::from_bytes(&schema, &message).unwrap();
+//! assert_eq!(
+//!     value,
+//!     Value::Seq(vec![
+//!         Value::Unsigned(4),
+//!         Value::Option(Some(Box::new(Value::Signed(-2)))),
+//!     ]),
+//! );
+//!
+//! assert_eq!(dynamic::to_allocvec(&schema, &value).unwrap(), message);
+//! ```
+
+extern crate alloc;
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use serde::{
+    ser::{SerializeMap, SerializeSeq, SerializeTuple, SerializeTupleVariant},
+    Deserialize, Serialize,
+};
+
+use crate::de::{deserializer::Deserializer, flavors::DeFlavor};
+use crate::error::{Error, Result};
+use crate::schema::{OwnedNamedType, OwnedSdmTy};
+use crate::ser::{
+    flavors::{AllocVec, SerFlavor, Slice},
+    serializer::Serializer,
+};
+
+/// A decoded postcard value, similar to a `serde_json::Value`.
+///
+/// Integers are widened to the largest signed or unsigned type, while types that contain
+/// no data on the wire (such as `()` or unit structs) are decoded as [`Value::Unit`].
+/// Tuples, tuple structs and tuple variants are represented as a [`Value::Seq`].
+///
+/// `Value` implements `Serialize` in a self-describing way, so it can be converted into
+/// formats such as JSON for display.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// No data, such as `()`, a unit struct or the contents of a unit variant
+    Unit,
+    /// A `bool`
+    Bool(bool),
+    /// Any signed integer
+    Signed(i128),
+    /// Any unsigned integer
+    Unsigned(u128),
+    /// An `f32`
+    F32(f32),
+    /// An `f64`
+    F64(f64),
+    /// A `char`
+    Char(char),
+    /// A string
+    String(String),
+    /// A byte array
+    Bytes(Vec<u8>),
+    /// An `Option`
+    Option(Option<Box<Value>>),
+    /// A sequence, tuple, tuple struct or the contents of a tuple variant
+    Seq(Vec<Value>),
+    /// A map, with keys and values in the order they appear in the message
+    Map(Vec<(Value, Value)>),
+    /// A struct, or the contents of a struct variant, with the names of the fields
+    Struct(Vec<(String, Value)>),
+    /// An enum variant, with the name of the variant, and its contents
+    Variant {
+        /// The name of the variant
+        name: String,
+        /// The contents of the variant
+        value: Box<Value>,
+    },
+}
+
+/// Decode a message from a byte slice, according to the given schema. The unused
+/// portion (if any) of the byte slice is not returned.
+pub fn from_bytes(schema: &OwnedNamedType, s: &[u8]) -> Result<Value> {
+    Ok(take_from_bytes(schema, s)?.0)
+}
+
+/// Decode a message from a byte slice, according to the given schema. The unused
+/// portion (if any) of the byte slice is returned for further usage
+pub fn take_from_bytes<'a>(schema: &OwnedNamedType, s: &'a [u8]) -> Result<(Value, &'a [u8])> {
+    let mut deserializer = Deserializer::from_bytes(s);
+    let value = deserialize(schema, &mut deserializer)?;
+    Ok((value, deserializer.finalize()?))
+}
+
+/// Decode a message from the given [`Deserializer`], according to the given schema.
+///
+/// This allows decoding messages from any flavor, or using any
+/// [`IntEncoding`](../enum.IntEncoding.html).
+///
+/// [`Deserializer`]: ../struct.Deserializer.html
+pub fn deserialize<'de, F>(
+    schema: &OwnedNamedType,
+    deserializer: &mut Deserializer<'de, F>,
+) -> Result<Value>
+where
+    F: DeFlavor<'de>,
+{
+    deserialize_ty(&schema.ty, deserializer)
+}
+
+fn deserialize_ty<'de, F>(ty: &OwnedSdmTy, de: &mut Deserializer<'de, F>) -> Result<Value>
+where
+    F: DeFlavor<'de>,
+{
+    Ok(match ty {
+        OwnedSdmTy::Bool => Value::Bool(bool::deserialize(&mut *de)?),
+        OwnedSdmTy::I8 => Value::Signed(i8::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::U8 => Value::Unsigned(u8::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::I16 => Value::Signed(i16::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::U16 => Value::Unsigned(u16::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::I32 => Value::Signed(i32::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::U32 => Value::Unsigned(u32::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::I64 | OwnedSdmTy::Isize => Value::Signed(i64::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::U64 | OwnedSdmTy::Usize => Value::Unsigned(u64::deserialize(&mut *de)?.into()),
+        OwnedSdmTy::I128 => Value::Signed(i128::deserialize(&mut *de)?),
+        OwnedSdmTy::U128 => Value::Unsigned(u128::deserialize(&mut *de)?),
+        OwnedSdmTy::F32 => Value::F32(f32::deserialize(&mut *de)?),
+        OwnedSdmTy::F64 => Value::F64(f64::deserialize(&mut *de)?),
+        OwnedSdmTy::Char => Value::Char(char::deserialize(&mut *de)?),
+        OwnedSdmTy::String => Value::String(String::deserialize(&mut *de)?),
+        OwnedSdmTy::ByteArray => {
+            let sz = de.try_take_varint()?;
+            Value::Bytes(de.try_take_n_temp(sz)?.to_vec())
+        }
+        OwnedSdmTy::Option(nt) => match u8::deserialize(&mut *de)? {
+            0 => Value::Option(None),
+            1 => Value::Option(Some(Box::new(deserialize_ty(&nt.ty, de)?))),
+            _ => return Err(Error::DeserializeBadOption),
+        },
+        OwnedSdmTy::Unit | OwnedSdmTy::UnitStruct | OwnedSdmTy::UnitVariant => Value::Unit,
+        OwnedSdmTy::NewtypeStruct(nt) | OwnedSdmTy::NewtypeVariant(nt) => {
+            deserialize_ty(&nt.ty, de)?
+        }
+        OwnedSdmTy::Seq(nt) => {
+            let len = de.try_take_varint()?;
+            let mut values = Vec::new();
+            for _ in 0..len {
+                values.push(deserialize_ty(&nt.ty, de)?);
+            }
+            Value::Seq(values)
+        }
+        OwnedSdmTy::Tuple(nts) | OwnedSdmTy::TupleStruct(nts) | OwnedSdmTy::TupleVariant(nts) => {
+            Value::Seq(
+                nts.iter()
+                    .map(|nt| deserialize_ty(&nt.ty, de))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        OwnedSdmTy::Map { key, val } => {
+            let len = de.try_take_varint()?;
+            let mut values = Vec::new();
+            for _ in 0..len {
+                let k = deserialize_ty(&key.ty, de)?;
+                let v = deserialize_ty(&val.ty, de)?;
+                values.push((k, v));
+            }
+            Value::Map(values)
+        }
+        OwnedSdmTy::Struct(nvs) | OwnedSdmTy::StructVariant(nvs) => Value::Struct(
+            nvs.iter()
+                .map(|nv| Ok((nv.name.clone(), deserialize_ty(&nv.ty.ty, de)?)))
+                .collect::<Result<_>>()?,
+        ),
+        OwnedSdmTy::Enum(variants) => {
+            let idx = de.try_take_varint()?;
+            let variant = variants.get(idx).ok_or(Error::DeserializeBadEnum)?;
+            Value::Variant {
+                name: variant.name.clone(),
+                value: Box::new(deserialize_ty(&variant.ty, de)?),
+            }
+        }
+    })
+}
+
+/// Encode a value to the given slice, according to the given schema.
+///
+/// If the value does not match the schema, [`Error::SerdeSerCustom`] is returned.
+///
+/// [`Error::SerdeSerCustom`]: ../enum.Error.html#variant.SerdeSerCustom
+pub fn to_slice<'a>(
+    schema: &OwnedNamedType,
+    value: &Value,
+    buf: &'a mut [u8],
+) -> Result<&'a mut [u8]> {
+    let mut serializer = Serializer::new(Slice::new(buf));
+    serialize(schema, value, &mut serializer)?;
+    serializer
+        .output
+        .release()
+        .map_err(|_| Error::SerializeBufferFull)
+}
+
+/// Encode a value to an `alloc::vec::Vec<u8>`, according to the given schema.
+///
+/// If the value does not match the schema, [`Error::SerdeSerCustom`] is returned.
+///
+/// [`Error::SerdeSerCustom`]: ../enum.Error.html#variant.SerdeSerCustom
+pub fn to_allocvec(schema: &OwnedNamedType, value: &Value) -> Result<Vec<u8>> {
+    let mut serializer = Serializer::new(AllocVec(Vec::new()));
+    serialize(schema, value, &mut serializer)?;
+    serializer
+        .output
+        .release()
+        .map_err(|_| Error::SerializeBufferFull)
+}
+
+/// Encode a value with the given [`Serializer`], according to the given schema.
+///
+/// This allows encoding messages with any flavor, or using any
+/// [`IntEncoding`](../enum.IntEncoding.html). The serializer is not released.
+///
+/// [`Serializer`]: ../struct.Serializer.html
+pub fn serialize<F>(
+    schema: &OwnedNamedType,
+    value: &Value,
+    serializer: &mut Serializer<F>,
+) -> Result<()>
+where
+    F: SerFlavor,
+{
+    WithSchema {
+        ty: &schema.ty,
+        value,
+    }
+    .serialize(serializer)
+}
+
+/// A value along with its schema, which serializes in the postcard wire format.
+///
+/// Identifiers are not available with `'static` lifetimes, so this must only be used with
+/// the postcard `Serializer`, which ignores them.
+struct WithSchema<'a> {
+    ty: &'a OwnedSdmTy,
+    value: &'a Value,
+}
+
+fn mismatch<E: serde::ser::Error>(ty: &OwnedSdmTy, value: &Value) -> E {
+    E::custom(format_args!(
+        "value {:?} does not match schema {:?}",
+        value, ty
+    ))
+}
+
+macro_rules! serialize_int {
+    ($serializer:ident, $method:ident, $ty:ty, $self:ident) => {{
+        let val = match *$self.value {
+            Value::Signed(v) => <$ty>::try_from(v).ok(),
+            Value::Unsigned(v) => <$ty>::try_from(v).ok(),
+            _ => None,
+        };
+        match val {
+            Some(v) => $serializer.$method(v),
+            None => Err(mismatch($self.ty, $self.value)),
+        }
+    }};
+}
+
+impl<'a> WithSchema<'a> {
+    fn with(&self, ty: &'a OwnedSdmTy, value: &'a Value) -> Self {
+        WithSchema { ty, value }
+    }
+
+    /// The fields of a tuple-like or struct-like value, which are serialized one after another
+    fn fields(&self, ty: &'a OwnedSdmTy, value: &'a Value) -> Option<Vec<WithSchema<'a>>> {
+        match (ty, value) {
+            (
+                OwnedSdmTy::Tuple(nts)
+                | OwnedSdmTy::TupleStruct(nts)
+                | OwnedSdmTy::TupleVariant(nts),
+                Value::Seq(values),
+            ) if nts.len() == values.len() => Some(
+                nts.iter()
+                    .zip(values.iter())
+                    .map(|(nt, v)| self.with(&nt.ty, v))
+                    .collect(),
+            ),
+            (OwnedSdmTy::Struct(nvs) | OwnedSdmTy::StructVariant(nvs), Value::Struct(values))
+                if nvs.len() == values.len() =>
+            {
+                nvs.iter()
+                    .zip(values.iter())
+                    .map(|(nv, (name, v))| (nv.name == *name).then(|| self.with(&nv.ty.ty, v)))
+                    .collect()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Serialize for WithSchema<'a> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use core::convert::TryFrom;
+
+        match (self.ty, self.value) {
+            (OwnedSdmTy::Bool, Value::Bool(v)) => serializer.serialize_bool(*v),
+            (OwnedSdmTy::I8, _) => serialize_int!(serializer, serialize_i8, i8, self),
+            (OwnedSdmTy::U8, _) => serialize_int!(serializer, serialize_u8, u8, self),
+            (OwnedSdmTy::I16, _) => serialize_int!(serializer, serialize_i16, i16, self),
+            (OwnedSdmTy::U16, _) => serialize_int!(serializer, serialize_u16, u16, self),
+            (OwnedSdmTy::I32, _) => serialize_int!(serializer, serialize_i32, i32, self),
+            (OwnedSdmTy::U32, _) => serialize_int!(serializer, serialize_u32, u32, self),
+            (OwnedSdmTy::I64 | OwnedSdmTy::Isize, _) => {
+                serialize_int!(serializer, serialize_i64, i64, self)
+            }
+            (OwnedSdmTy::U64 | OwnedSdmTy::Usize, _) => {
+                serialize_int!(serializer, serialize_u64, u64, self)
+            }
+            (OwnedSdmTy::I128, _) => serialize_int!(serializer, serialize_i128, i128, self),
+            (OwnedSdmTy::U128, _) => serialize_int!(serializer, serialize_u128, u128, self),
+            (OwnedSdmTy::F32, Value::F32(v)) => serializer.serialize_f32(*v),
+            (OwnedSdmTy::F64, Value::F64(v)) => serializer.serialize_f64(*v),
+            (OwnedSdmTy::Char, Value::Char(v)) => serializer.serialize_char(*v),
+            (OwnedSdmTy::String, Value::String(v)) => serializer.serialize_str(v),
+            (OwnedSdmTy::ByteArray, Value::Bytes(v)) => serializer.serialize_bytes(v),
+            (OwnedSdmTy::Option(_), Value::Option(None)) => serializer.serialize_none(),
+            (OwnedSdmTy::Option(nt), Value::Option(Some(v))) => {
+                serializer.serialize_some(&self.with(&nt.ty, v))
+            }
+            (OwnedSdmTy::Unit | OwnedSdmTy::UnitStruct | OwnedSdmTy::UnitVariant, Value::Unit) => {
+                serializer.serialize_unit()
+            }
+            (OwnedSdmTy::NewtypeStruct(nt) | OwnedSdmTy::NewtypeVariant(nt), v) => {
+                self.with(&nt.ty, v).serialize(serializer)
+            }
+            (OwnedSdmTy::Seq(nt), Value::Seq(values)) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for v in values {
+                    seq.serialize_element(&self.with(&nt.ty, v))?;
+                }
+                seq.end()
+            }
+            (OwnedSdmTy::Map { key, val }, Value::Map(values)) => {
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+                for (k, v) in values {
+                    map.serialize_entry(&self.with(&key.ty, k), &self.with(&val.ty, v))?;
+                }
+                map.end()
+            }
+            (OwnedSdmTy::Enum(variants), Value::Variant { name, value }) => {
+                let (idx, variant) = variants
+                    .iter()
+                    .enumerate()
+                    .find(|(_, var)| var.name == *name)
+                    .ok_or_else(|| mismatch(self.ty, self.value))?;
+                let idx = u32::try_from(idx).map_err(|_| mismatch(self.ty, self.value))?;
+                match (&variant.ty, &**value) {
+                    (OwnedSdmTy::UnitVariant, Value::Unit) => {
+                        serializer.serialize_unit_variant("", idx, "")
+                    }
+                    (OwnedSdmTy::NewtypeVariant(nt), value) => {
+                        serializer.serialize_newtype_variant("", idx, "", &self.with(&nt.ty, value))
+                    }
+                    (ty, value) => {
+                        let fields = self.fields(ty, value).ok_or_else(|| mismatch(ty, value))?;
+                        let mut tv =
+                            serializer.serialize_tuple_variant("", idx, "", fields.len())?;
+                        for field in fields.iter() {
+                            tv.serialize_field(field)?;
+                        }
+                        tv.end()
+                    }
+                }
+            }
+            (ty, value) => {
+                // Tuples and structs are the same on the wire
+                let fields = self.fields(ty, value).ok_or_else(|| mismatch(ty, value))?;
+                let mut tup = serializer.serialize_tuple(fields.len())?;
+                for field in fields.iter() {
+                    tup.serialize_element(field)?;
+                }
+                tup.end()
+            }
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use core::convert::TryFrom;
+
+        match self {
+            Value::Unit => serializer.serialize_unit(),
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::Signed(v) => match i64::try_from(*v) {
+                Ok(v) => serializer.serialize_i64(v),
+                Err(_) => serializer.serialize_i128(*v),
+            },
+            Value::Unsigned(v) => match u64::try_from(*v) {
+                Ok(v) => serializer.serialize_u64(v),
+                Err(_) => serializer.serialize_u128(*v),
+            },
+            Value::F32(v) => serializer.serialize_f32(*v),
+            Value::F64(v) => serializer.serialize_f64(*v),
+            Value::Char(v) => serializer.serialize_char(*v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::Option(None) => serializer.serialize_none(),
+            Value::Option(Some(v)) => serializer.serialize_some(v),
+            Value::Seq(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for v in values {
+                    seq.serialize_element(v)?;
+                }
+                seq.end()
+            }
+            Value::Map(values) => {
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+                for (k, v) in values {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            Value::Struct(values) => {
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+                for (k, v) in values {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            Value::Variant { name, value } => match **value {
+                Value::Unit => serializer.serialize_str(name),
+                ref value => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(name, value)?;
+                    map.end()
+                }
+            },
+        }
+    }
+}
@@ -2,6 +2,15 @@
 
 use core::fmt::{Display, Formatter};
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
 /// This is the error type used by Postcard
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
@@ -14,6 +23,8 @@ pub enum Error {
     SerializeBufferFull,
     /// The length of a sequence must be known
     SerializeSeqLengthUnknown,
+    /// The writer returned an I/O error
+    SerializeIoError,
     /// Hit the end of buffer, expected more data
     DeserializeUnexpectedEnd,
     /// Found a varint that didn't terminate. Is the usize too big for this platform?
@@ -30,39 +41,63 @@ pub enum Error {
     DeserializeBadEnum,
     /// The original data was not well encoded
     DeserializeBadEncoding,
-    /// Serde Serialization Error
-    SerdeSerCustom,
-    /// Serde Deserialization Error
-    SerdeDeCustom,
+    /// The checksum of the message did not match
+    DeserializeBadCrc,
+    /// The reader returned an I/O error
+    DeserializeIoError,
+    /// The scratch buffer is too small to hold the borrowed data
+    DeserializeScratchTooSmall,
+    /// The frame did not fit into the deserialize buffer
+    DeserializeBufferFull,
+    /// Serde Serialization Error, with the message passed to `serde::ser::Error::custom()`
+    SerdeSerCustom(CustomMessage),
+    /// Serde Deserialization Error, with the message passed to `serde::de::Error::custom()`
+    SerdeDeCustom(CustomMessage),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         use Error::*;
-        write!(
-            f,
-            "{}",
-            match self {
-                WontImplement => "This is a feature that PostCard will never implement",
-                NotYetImplemented => {
-                    "This is a feature that Postcard intends to support, but does not yet"
-                }
-                SerializeBufferFull => "The serialize buffer is full",
-                SerializeSeqLengthUnknown => "The length of a sequence must be known",
-                DeserializeUnexpectedEnd => "Hit the end of buffer, expected more data",
-                DeserializeBadVarint => {
-                    "Found a varint that didn't terminate. Is the usize too big for this platform?"
-                }
-                DeserializeBadBool => "Found a bool that wasn't 0 or 1",
-                DeserializeBadChar => "Found an invalid unicode char",
-                DeserializeBadUtf8 => "Tried to parse invalid utf-8",
-                DeserializeBadOption => "Found an Option discriminant that wasn't 0 or 1",
-                DeserializeBadEnum => "Found an enum discriminant that was > u32::max_value()",
-                DeserializeBadEncoding => "The original data was not well encoded",
-                SerdeSerCustom => "Serde Serialization Error",
-                SerdeDeCustom => "Serde Deserialization Error",
+        match self {
+            SerdeSerCustom(msg) | SerdeDeCustom(msg) if !msg.as_str().is_empty() => {
+                return write!(f, "{}: {}", self.description(), msg);
+            }
+            _ => {}
+        }
+        f.write_str(self.description())
+    }
+}
+
+impl Error {
+    fn description(&self) -> &'static str {
+        use Error::*;
+        match self {
+            WontImplement => "This is a feature that PostCard will never implement",
+            NotYetImplemented => {
+                "This is a feature that Postcard intends to support, but does not yet"
+            }
+            SerializeBufferFull => "The serialize buffer is full",
+            SerializeSeqLengthUnknown => "The length of a sequence must be known",
+            SerializeIoError => "The writer returned an I/O error",
+            DeserializeUnexpectedEnd => "Hit the end of buffer, expected more data",
+            DeserializeBadVarint => {
+                "Found a varint that didn't terminate. Is the usize too big for this platform?"
             }
-        )
+            DeserializeBadBool => "Found a bool that wasn't 0 or 1",
+            DeserializeBadChar => "Found an invalid unicode char",
+            DeserializeBadUtf8 => "Tried to parse invalid utf-8",
+            DeserializeBadOption => "Found an Option discriminant that wasn't 0 or 1",
+            DeserializeBadEnum => "Found an enum discriminant that was > u32::max_value()",
+            DeserializeBadEncoding => "The original data was not well encoded",
+            DeserializeBadCrc => "The checksum of the message did not match",
+            DeserializeIoError => "The reader returned an I/O error",
+            DeserializeScratchTooSmall => {
+                "The scratch buffer is too small to hold the borrowed data"
+            }
+            DeserializeBufferFull => "The frame did not fit into the deserialize buffer",
+            SerdeSerCustom(_) => "Serde Serialization Error",
+            SerdeDeCustom(_) => "Serde Deserialization Error",
+        }
     }
 }
 
@@ -70,21 +105,189 @@ impl Display for Error {
 pub type Result<T> = ::core::result::Result<T, Error>;
 
 impl serde::ser::Error for Error {
-    fn custom<T>(_msg: T) -> Self
+    fn custom<T>(msg: T) -> Self
     where
         T: Display,
     {
-        Error::SerdeSerCustom
+        Error::SerdeSerCustom(CustomMessage::new(msg))
     }
 }
 
 impl serde::de::Error for Error {
-    fn custom<T>(_msg: T) -> Self
+    fn custom<T>(msg: T) -> Self
     where
         T: Display,
     {
-        Error::SerdeDeCustom
+        Error::SerdeDeCustom(CustomMessage::new(msg))
     }
 }
 
 impl serde::ser::StdError for Error {}
+
+/// The maximum length in bytes of a [`CustomMessage`] with the `heapless-custom-message`
+/// feature, and without the `alloc` feature
+pub const CUSTOM_MESSAGE_CAPACITY: usize = 32;
+
+/// The message of a custom serde error.
+///
+/// With the `alloc` feature, the complete message is retained. Otherwise, with the default
+/// `heapless-custom-message` feature, the message is truncated to [`CUSTOM_MESSAGE_CAPACITY`]
+/// bytes. Without either feature, the message is discarded, and a `CustomMessage` takes up no
+/// space, so that [`Error`] stays a single byte.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CustomMessage {
+    #[cfg(feature = "alloc")]
+    msg: String,
+    #[cfg(all(feature = "heapless-custom-message", not(feature = "alloc")))]
+    msg: heapless::String<CUSTOM_MESSAGE_CAPACITY>,
+}
+
+impl CustomMessage {
+    /// Create a new message, truncating it if necessary
+    pub fn new<T: Display>(msg: T) -> Self {
+        #[cfg(feature = "alloc")]
+        let out = CustomMessage {
+            msg: msg.to_string(),
+        };
+
+        #[cfg(all(feature = "heapless-custom-message", not(feature = "alloc")))]
+        let out = {
+            let mut out = CustomMessage::default();
+            // Running out of space is fine, the message is truncated
+            let _ = core::fmt::write(&mut Truncate(&mut out.msg), format_args!("{}", msg));
+            out
+        };
+
+        #[cfg(not(any(feature = "alloc", feature = "heapless-custom-message")))]
+        let out = CustomMessage {};
+
+        out
+    }
+
+    /// The retained message, or an empty string if it was discarded
+    pub fn as_str(&self) -> &str {
+        #[cfg(any(feature = "alloc", feature = "heapless-custom-message"))]
+        return &self.msg;
+        #[cfg(not(any(feature = "alloc", feature = "heapless-custom-message")))]
+        return "";
+    }
+}
+
+#[cfg(all(feature = "heapless-custom-message", not(feature = "alloc")))]
+struct Truncate<'a>(&'a mut heapless::String<CUSTOM_MESSAGE_CAPACITY>);
+
+#[cfg(all(feature = "heapless-custom-message", not(feature = "alloc")))]
+impl core::fmt::Write for Truncate<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // Push character-wise, so that truncated messages are still valid utf-8
+        for c in s.chars() {
+            self.0.push(c).map_err(|_| core::fmt::Error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CustomMessage {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "use-defmt")]
+impl defmt::Format for CustomMessage {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", self.as_str())
+    }
+}
+
+/// A deserialization error, along with the location in the input at which it occurred.
+///
+/// This is returned by [`from_bytes_detailed()`](fn.from_bytes_detailed.html) and
+/// [`take_from_bytes_detailed()`](fn.take_from_bytes_detailed.html). With the `alloc`
+/// feature, it also contains the path of the struct fields, enum variants and sequence
+/// elements that were being decoded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DetailedError {
+    pub(crate) kind: Error,
+    pub(crate) offset: usize,
+    #[cfg(feature = "alloc")]
+    pub(crate) path: Vec<PathSegment>,
+}
+
+impl DetailedError {
+    /// The kind of error that occurred
+    pub fn kind(&self) -> &Error {
+        &self.kind
+    }
+
+    /// The offset into the input at which the error was detected, which is the number of bytes
+    /// consumed up to that point. For an invalid value, this is usually the offset just past
+    /// the value. If the input ended early, this is the offset of the incomplete value.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The path to the value that was being decoded when the error occurred, from the
+    /// outermost to the innermost value
+    #[cfg(feature = "alloc")]
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+}
+
+impl From<DetailedError> for Error {
+    fn from(err: DetailedError) -> Self {
+        err.kind
+    }
+}
+
+impl Display for DetailedError {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+
+        #[cfg(feature = "alloc")]
+        if !self.path.is_empty() {
+            f.write_str(" in `")?;
+            for (i, segment) in self.path.iter().enumerate() {
+                match (i, segment) {
+                    (0, PathSegment::Field(name)) | (0, PathSegment::Variant(name)) => {
+                        f.write_str(name)?
+                    }
+                    (_, PathSegment::Field(name)) => write!(f, ".{}", name)?,
+                    (_, PathSegment::Variant(name)) => write!(f, "::{}", name)?,
+                    (_, PathSegment::Index(idx)) => write!(f, "[{}]", idx)?,
+                }
+            }
+            f.write_str("`")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl serde::ser::StdError for DetailedError {}
+
+/// One step of the path to a value, as recorded in a [`DetailedError`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub enum PathSegment {
+    /// The field of a struct, or struct variant, with the given name
+    Field(&'static str),
+    /// The enum variant with the given name
+    Variant(&'static str),
+    /// The element of a sequence, tuple or tuple struct with the given index, or the
+    /// key or value of the map entry with the given index
+    Index(usize),
+}
+
+#[cfg(all(test, not(any(feature = "alloc", feature = "heapless-custom-message"))))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn error_size() {
+        // Without a retained message, `Error` is as small as a field-less enum
+        assert_eq!(core::mem::size_of::<CustomMessage>(), 0);
+        assert_eq!(core::mem::size_of::<Error>(), 1);
+    }
+}
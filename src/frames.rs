@@ -0,0 +1,200 @@
+//! Iterators over the COBS frames of a byte stream.
+
+use crate::de::from_bytes_cobs;
+use crate::error::Result;
+use core::marker::PhantomData;
+use serde::Deserialize;
+
+#[cfg(feature = "use-std")]
+use crate::accumulator::{decoders, CobsAccumulator, StatsMonitor};
+#[cfg(feature = "use-std")]
+use crate::error::Error;
+#[cfg(feature = "use-std")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "use-std")]
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+
+/// An iterator over the COBS frames in a byte slice, which deserializes each frame into a `T`.
+///
+/// Each frame is decoded in place, so `T` may borrow from the slice. Empty frames are skipped.
+/// When a frame can not be decoded, the error is returned, and iteration continues after the
+/// next zero byte. The number of bytes discarded this way is available from
+/// [`discarded()`](CobsFrames::discarded).
+///
+/// A partial frame at the end of the slice, without a terminating zero byte, is not returned,
+/// but is available from [`remainder()`](CobsFrames::remainder).
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{to_slice_cobs, CobsFrames, Error};
+///
+/// let mut buf = [0u8; 32];
+/// let mut len = to_slice_cobs("first", &mut buf).unwrap().len();
+/// buf[len..][..3].copy_from_slice(&[0x02, 0xFF, 0x00]);
+/// len += 3;
+/// len += to_slice_cobs("second", &mut buf[len..]).unwrap().len();
+/// buf[len] = 0x04;
+///
+/// let mut frames = CobsFrames::<&str>::new(&mut buf[..len + 1]);
+/// assert_eq!(frames.next(), Some(Ok("first")));
+/// assert_eq!(frames.next(), Some(Err(Error::DeserializeUnexpectedEnd)));
+/// assert_eq!(frames.next(), Some(Ok("second")));
+/// assert_eq!(frames.next(), None);
+///
+/// assert_eq!(frames.discarded(), 3);
+/// assert_eq!(frames.remainder(), &[0x04]);
+/// ```
+pub struct CobsFrames<'a, T> {
+    buf: &'a mut [u8],
+    discarded: usize,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> CobsFrames<'a, T> {
+    /// Create a new iterator over the frames in `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        CobsFrames {
+            buf,
+            discarded: 0,
+            _t: PhantomData,
+        }
+    }
+
+    /// The number of bytes of frames which could not be decoded, including their terminating
+    /// zero bytes.
+    pub fn discarded(&self) -> usize {
+        self.discarded
+    }
+
+    /// The part of the slice which has not been decoded yet.
+    pub fn remainder(self) -> &'a mut [u8] {
+        self.buf
+    }
+}
+
+impl<'a, T> Iterator for CobsFrames<'a, T>
+where
+    T: Deserialize<'a>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let end = self.buf.iter().position(|b| *b == 0)? + 1;
+            let (frame, rest) = core::mem::take(&mut self.buf).split_at_mut(end);
+            self.buf = rest;
+
+            // Skip empty frames
+            if end == 1 {
+                continue;
+            }
+
+            let res = from_bytes_cobs(frame);
+            if res.is_err() {
+                self.discarded += end;
+            }
+            return Some(res);
+        }
+    }
+}
+
+/// An iterator over the COBS frames read from a [`Read`]er, which deserializes each frame into
+/// a `T`.
+///
+/// Frames are collected with a [`CobsAccumulator`] of `N` bytes, see its documentation for the
+/// handling of frames that are too large or invalid. These are returned as errors, and
+/// iteration continues with the next frame. The number of bytes discarded this way is available
+/// from [`discarded()`](CobsStream::discarded).
+///
+/// Iteration ends when the reader reaches its end, or after returning an I/O error as
+/// [`Error::DeserializeIoError`]. A partial frame at the end of the input is ignored.
+///
+/// This type is only available when the (non-default) `use-std` feature is active.
+///
+/// # Examples
+///
+/// ```rust
+/// use postcard::{to_stdvec_cobs, CobsStream, Error};
+///
+/// let mut input = to_stdvec_cobs(&(1u8, 2u16)).unwrap();
+/// input.extend_from_slice(&[0x02, 0xFF, 0x00]);
+/// input.extend(to_stdvec_cobs(&(3u8, 4u16)).unwrap());
+///
+/// let stream = CobsStream::<_, (u8, u16), 32>::new(&input[..]);
+/// let frames: Vec<_> = stream.collect();
+/// assert_eq!(
+///     frames,
+///     &[Ok((1, 2)), Err(Error::DeserializeUnexpectedEnd), Ok((3, 4))]
+/// );
+/// ```
+///
+/// [`Read`]: std::io::Read
+/// [`Error::DeserializeIoError`]: enum.Error.html#variant.DeserializeIoError
+#[cfg(feature = "use-std")]
+pub struct CobsStream<R, T, const N: usize> {
+    reader: BufReader<R>,
+    acc: CobsAccumulator<N, StatsMonitor>,
+    done: bool,
+    _t: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "use-std")]
+impl<R: Read, T, const N: usize> CobsStream<R, T, N> {
+    /// Create a new iterator over the frames read from `reader`. The reader is buffered
+    /// internally, so it does not need to implement [`BufRead`].
+    pub fn new(reader: R) -> Self {
+        CobsStream {
+            reader: BufReader::new(reader),
+            acc: CobsAccumulator::with_monitor(decoders::Cobs::new(), StatsMonitor::new()),
+            done: false,
+            _t: PhantomData,
+        }
+    }
+
+    /// The number of bytes of frames which were too large or could not be decoded, including
+    /// their terminating zero bytes.
+    pub fn discarded(&self) -> usize {
+        self.acc.monitor().stats().bytes_discarded as usize
+    }
+
+    /// Get the reader back. Any data that was buffered, but not yet decoded, is lost.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+#[cfg(feature = "use-std")]
+impl<R: Read, T, const N: usize> Iterator for CobsStream<R, T, N>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let input = match self.reader.fill_buf() {
+                Ok([]) => break,
+                Ok(input) => input,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.done = true;
+                    return Some(Err(Error::DeserializeIoError));
+                }
+            };
+
+            let len = input.len();
+            let (res, used) = match self.acc.feed_frame::<T>(input) {
+                Some((res, remaining)) => (Some(res), len - remaining.len()),
+                None => (None, len),
+            };
+            self.reader.consume(used);
+            if let Some(res) = res {
+                return Some(res.map_err(Error::from));
+            }
+        }
+
+        self.done = true;
+        None
+    }
+}
@@ -0,0 +1,106 @@
+//! Constants and helpers for HDLC-like asynchronous framing (RFC 1662).
+
+use crate::error::{Error, Result};
+
+/// Marks the start and end of a frame
+pub(crate) const FLAG: u8 = 0x7E;
+/// Starts an escape sequence
+pub(crate) const ESC: u8 = 0x7D;
+/// Escaped bytes are transmitted XORed with this value, following `ESC`
+pub(crate) const XOR: u8 = 0x20;
+/// The size of the frame check sequence
+pub(crate) const FCS_SIZE: usize = 2;
+
+/// The 16-bit frame check sequence (CRC-16/X-25) used by HDLC
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub(crate) struct Fcs16(u16);
+
+impl Fcs16 {
+    pub(crate) const fn new() -> Self {
+        Fcs16(0xFFFF)
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 ^= u16::from(*byte);
+            for _ in 0..8 {
+                self.0 = if self.0 & 1 != 0 {
+                    (self.0 >> 1) ^ 0x8408
+                } else {
+                    self.0 >> 1
+                };
+            }
+        }
+    }
+
+    /// The value of the frame check sequence, transmitted in little endian order
+    pub(crate) fn finish(self) -> u16 {
+        !self.0
+    }
+}
+
+/// Decode the first HDLC frame in `buf` in place, skipping any leading flag bytes.
+/// If there is no terminating flag byte, the frame ends with the buffer.
+///
+/// Returns the range of `buf` holding the decoded message, without the frame check
+/// sequence, and the number of bytes of `buf` that were consumed.
+pub(crate) fn decode_in_place(buf: &mut [u8]) -> Result<(core::ops::Range<usize>, usize)> {
+    let start = buf.iter().position(|b| *b != FLAG).unwrap_or(buf.len());
+    let mut dest = start;
+    let mut src = start;
+
+    while src < buf.len() {
+        let byte = buf[src];
+        src += 1;
+        buf[dest] = match byte {
+            FLAG => break,
+            ESC => {
+                let escaped = buf.get(src).ok_or(Error::DeserializeBadEncoding)?;
+                src += 1;
+                unescape(*escaped)?
+            }
+            _ => byte,
+        };
+        dest += 1;
+    }
+
+    let len = check_fcs(&buf[start..dest])?;
+    Ok((start..start + len, src))
+}
+
+/// Get the original value of an escaped byte. A flag byte following `ESC` aborts the frame.
+pub(crate) fn unescape(byte: u8) -> Result<u8> {
+    match byte {
+        FLAG => Err(Error::DeserializeBadEncoding),
+        _ => Ok(byte ^ XOR),
+    }
+}
+
+/// Check the frame check sequence at the end of a decoded frame, returning the length
+/// of the message that precedes it.
+pub(crate) fn check_fcs(frame: &[u8]) -> Result<usize> {
+    let len = frame
+        .len()
+        .checked_sub(FCS_SIZE)
+        .ok_or(Error::DeserializeUnexpectedEnd)?;
+    let mut fcs = Fcs16::new();
+    fcs.update(&frame[..len]);
+    if fcs.finish().to_le_bytes() == frame[len..] {
+        Ok(len)
+    } else {
+        Err(Error::DeserializeBadCrc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fcs_check_value() {
+        let mut fcs = Fcs16::new();
+        fcs.update(b"123456789");
+        assert_eq!(fcs.finish(), 0x906E);
+    }
+}
@@ -33,6 +33,27 @@
 //!
 //! [Varint]: https://developers.google.com/protocol-buffers/docs/encoding
 //!
+//! ## Integer Encoding
+//!
+//! By default, integers are encoded as fixed width little endian values, e.g. a `u32` always takes
+//! four bytes on the wire.
+//!
+//! When most values are small, the [`IntEncoding::Varint`] mode can be used instead, which encodes
+//! all integers wider than a byte as varints, and signed integers with zigzag encoding first. This
+//! mode is not recorded in the message, so both sides must agree to use it, for example by using
+//! [`to_slice_varint()`] together with [`from_bytes_varint()`].
+//!
+//! ```rust
+//! use postcard::{from_bytes_varint, to_slice_varint};
+//!
+//! let mut buf = [0u8; 32];
+//! let used = to_slice_varint(&(5u32, -3i64), &mut buf).unwrap();
+//! assert_eq!(used, &[0x05, 0x05]);
+//!
+//! let out: (u32, i64) = from_bytes_varint(used).unwrap();
+//! assert_eq!(out, (5, -3));
+//! ```
+//!
 //! ## Example - Serialization/Deserialization
 //!
 //! Postcard can serialize and deserialize messages similar to other `serde` formats.
@@ -121,6 +142,7 @@
 //! and placing the output in a byte slice (a "storage flavor").
 //!
 //! Users of `postcard` can define their own Flavors that can be combined with existing Flavors.
+//! Deserialization supports the same concept, see the [`de_flavors`] module for more details.
 //!
 //! ```rust
 //! use postcard::{
@@ -176,22 +198,89 @@
 #![warn(missing_docs)]
 
 mod accumulator;
+#[cfg(feature = "use-crc")]
+mod checksum;
+#[cfg(feature = "tokio-util")]
+mod codec;
 mod de;
+#[cfg(feature = "alloc")]
+pub mod dynamic;
 mod error;
+mod frames;
+mod hdlc;
+mod max_size;
+pub mod schema;
 mod ser;
+mod slip;
 mod traits;
 mod varint;
 
-pub use accumulator::{CobsAccumulator, FeedResult};
+pub use accumulator::{
+    decoders, Accumulator, AccumulatorStats, CobsAccumulator, FeedResult, FrameError, FrameMonitor,
+    HdlcAccumulator, LengthPrefixAccumulator, SlipAccumulator, StatsMonitor,
+};
+#[cfg(feature = "alloc")]
+pub use accumulator::{AllocAccumulator, AllocCobsAccumulator};
+#[cfg(feature = "use-crc")]
+pub use checksum::CrcDigest;
+#[cfg(feature = "tokio-util")]
+pub use codec::{CodecError, PostcardCobsCodec};
+pub use de::flavors as de_flavors;
+#[cfg(feature = "embedded-io-async")]
+pub use de::from_async_reader;
+#[cfg(all(feature = "bytes", feature = "use-std"))]
+pub use de::from_buf;
+#[cfg(feature = "bytes")]
+pub use de::from_buf_chunks;
+#[cfg(feature = "embedded-io")]
+pub use de::from_eio;
+#[cfg(feature = "futures-io")]
+pub use de::from_futures_reader;
+#[cfg(feature = "use-std")]
+pub use de::from_io;
 pub use de::{
-    deserializer::Deserializer, from_bytes, from_bytes_cobs, take_from_bytes, take_from_bytes_cobs,
+    deserializer::Deserializer, from_bytes, from_bytes_cobs, from_bytes_detailed, from_bytes_hdlc,
+    from_bytes_length_prefix, from_bytes_slip, from_bytes_varint, take_from_bytes,
+    take_from_bytes_cobs, take_from_bytes_detailed, take_from_bytes_hdlc,
+    take_from_bytes_length_prefix, take_from_bytes_slip, take_from_bytes_varint,
+};
+#[cfg(feature = "use-crc")]
+pub use de::{from_bytes_crc, take_from_bytes_crc};
+pub use error::{
+    CustomMessage, DetailedError, Error, PathSegment, Result, CUSTOM_MESSAGE_CAPACITY,
+};
+pub use frames::CobsFrames;
+#[cfg(feature = "use-std")]
+pub use frames::CobsStream;
+pub use max_size::{max_size_cobs, MaxSize};
+#[cfg(feature = "derive")]
+pub use postcard_derive::{MaxSize, Schema};
+pub use schema::Schema;
+#[cfg(all(feature = "alloc", feature = "use-crc"))]
+pub use ser::to_allocvec_crc;
+#[cfg(feature = "bytes")]
+pub use ser::to_bufmut;
+#[cfg(feature = "embedded-io")]
+pub use ser::to_eio;
+#[cfg(feature = "use-crc")]
+pub use ser::to_slice_crc;
+#[cfg(all(feature = "use-std", feature = "use-crc"))]
+pub use ser::to_stdvec_crc;
+#[cfg(all(feature = "heapless", feature = "use-crc"))]
+pub use ser::to_vec_crc;
+pub use ser::{
+    flavors, serialize_with_flavor, serialize_with_flavor_varint, serialized_size,
+    serializer::{SeqSerializer, Serializer},
+    to_slice, to_slice_cobs, to_slice_hdlc, to_slice_length_prefix, to_slice_slip, to_slice_varint,
 };
-pub use error::{Error, Result};
-pub use ser::{flavors, serialize_with_flavor, serializer::Serializer, to_slice, to_slice_cobs};
 #[cfg(feature = "alloc")]
 pub use ser::{to_allocvec, to_allocvec_cobs};
+#[cfg(feature = "embedded-io-async")]
+pub use ser::{to_async_writer, to_async_writer_cobs};
+#[cfg(feature = "futures-io")]
+pub use ser::{to_futures_writer, to_futures_writer_cobs};
 #[cfg(feature = "use-std")]
-pub use ser::{to_stdvec, to_stdvec_cobs};
+pub use ser::{to_io, to_stdvec, to_stdvec_cobs};
 #[cfg(feature = "heapless")]
 pub use ser::{to_vec, to_vec_cobs};
 pub use traits::string::{PostcardString, PostcardStringRW};
@@ -199,3 +288,4 @@ pub use traits::string::{PostcardString, PostcardStringRW};
 pub use traits::vec::{
     Collection, CollectionFamily, CollectionMut, IterMut, PostcardVec, PostcardVecMut, VecFamily,
 };
+pub use varint::IntEncoding;
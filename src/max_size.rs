@@ -0,0 +1,220 @@
+//! Calculating the maximum serialized size of a type at compile time.
+
+use core::marker::PhantomData;
+
+use crate::varint::varint_max;
+
+/// This trait is used to enforce the maximum size required to
+/// store the serialization of a given type.
+///
+/// The size is a worst-case value: it is large enough for messages using either
+/// [`IntEncoding`], and for the largest variant of an enum, so it can be used to size
+/// buffers for serialization or for the [`CobsAccumulator`], e.g. `[u8; T::POSTCARD_MAX_SIZE]`.
+/// When the message is COBS encoded, use [`max_size_cobs()`] to account for the overhead.
+///
+/// Types with no fixed upper size, such as `&str` or `&[u8]`, do not implement this trait.
+///
+/// With the (non-default) `derive` feature, `MaxSize` can be derived for structs and enums
+/// whose fields all implement `MaxSize`:
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// use postcard::MaxSize;
+///
+/// #[derive(MaxSize)]
+/// struct Reading {
+///     sensor: u8,
+///     value: Option<i32>,
+/// }
+///
+/// #[derive(MaxSize)]
+/// enum Message {
+///     Ping,
+///     Readings([Reading; 4]),
+/// }
+///
+/// assert_eq!(Reading::POSTCARD_MAX_SIZE, 1 + 1 + 5);
+/// assert_eq!(Message::POSTCARD_MAX_SIZE, 1 + 4 * 7);
+///
+/// let mut buf = [0u8; Message::POSTCARD_MAX_SIZE];
+/// # }
+/// ```
+///
+/// [`IntEncoding`]: enum.IntEncoding.html
+/// [`CobsAccumulator`]: struct.CobsAccumulator.html
+/// [`max_size_cobs()`]: fn.max_size_cobs.html
+pub trait MaxSize {
+    /// The maximum possible size that the serialization of this
+    /// type can have, in bytes.
+    const POSTCARD_MAX_SIZE: usize;
+}
+
+/// The maximum size of a message of `size` bytes, after it has been COBS encoded,
+/// including the terminating sentinel `0x00` byte.
+///
+/// ```rust
+/// use postcard::{max_size_cobs, MaxSize};
+///
+/// let mut buf = [0u8; max_size_cobs(<[u32; 2]>::POSTCARD_MAX_SIZE)];
+/// postcard::to_slice_cobs(&[u32::MAX; 2], &mut buf).unwrap();
+/// ```
+pub const fn max_size_cobs(size: usize) -> usize {
+    // One overhead byte for every (started) block of 254 bytes, plus the sentinel
+    size + (size / 254) + 1 + 1
+}
+
+/// The number of bytes used to encode the given value as a varint
+const fn varint_size(value: usize) -> usize {
+    let bits = (usize::BITS - value.leading_zeros()) as usize;
+    if bits == 0 {
+        1
+    } else {
+        bits.div_ceil(7)
+    }
+}
+
+macro_rules! impl_max_size {
+    ($($ty:ty => $size:expr),* $(,)?) => {
+        $(
+            impl MaxSize for $ty {
+                const POSTCARD_MAX_SIZE: usize = $size;
+            }
+        )*
+    };
+}
+
+impl_max_size! {
+    () => 0,
+    bool => 1,
+    u8 => 1,
+    i8 => 1,
+    u16 => varint_max::<u16>(),
+    i16 => varint_max::<i16>(),
+    u32 => varint_max::<u32>(),
+    i32 => varint_max::<i32>(),
+    u64 => varint_max::<u64>(),
+    i64 => varint_max::<i64>(),
+    u128 => varint_max::<u128>(),
+    i128 => varint_max::<i128>(),
+    // `usize` and `isize` are sent as 64-bit integers
+    usize => varint_max::<u64>(),
+    isize => varint_max::<i64>(),
+    f32 => 4,
+    f64 => 8,
+    // A length prefix, followed by up to four bytes of UTF-8
+    char => 1 + 4,
+}
+
+impl<T: ?Sized> MaxSize for PhantomData<T> {
+    const POSTCARD_MAX_SIZE: usize = 0;
+}
+
+impl<T: MaxSize, const N: usize> MaxSize for [T; N] {
+    const POSTCARD_MAX_SIZE: usize = T::POSTCARD_MAX_SIZE * N;
+}
+
+impl<T: MaxSize> MaxSize for Option<T> {
+    const POSTCARD_MAX_SIZE: usize = 1 + T::POSTCARD_MAX_SIZE;
+}
+
+impl<T: MaxSize, E: MaxSize> MaxSize for Result<T, E> {
+    const POSTCARD_MAX_SIZE: usize = 1 + if T::POSTCARD_MAX_SIZE > E::POSTCARD_MAX_SIZE {
+        T::POSTCARD_MAX_SIZE
+    } else {
+        E::POSTCARD_MAX_SIZE
+    };
+}
+
+macro_rules! impl_max_size_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: MaxSize),+> MaxSize for ($($ty,)+) {
+            const POSTCARD_MAX_SIZE: usize = 0 $(+ $ty::POSTCARD_MAX_SIZE)+;
+        }
+    };
+}
+
+impl_max_size_tuple!(A);
+impl_max_size_tuple!(A, B);
+impl_max_size_tuple!(A, B, C);
+impl_max_size_tuple!(A, B, C, D);
+impl_max_size_tuple!(A, B, C, D, E);
+impl_max_size_tuple!(A, B, C, D, E, F);
+impl_max_size_tuple!(A, B, C, D, E, F, G);
+impl_max_size_tuple!(A, B, C, D, E, F, G, H);
+impl_max_size_tuple!(A, B, C, D, E, F, G, H, I);
+impl_max_size_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_max_size_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_max_size_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(feature = "heapless")]
+impl<T: MaxSize, const N: usize> MaxSize for heapless::Vec<T, N> {
+    const POSTCARD_MAX_SIZE: usize = varint_size(N) + T::POSTCARD_MAX_SIZE * N;
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> MaxSize for heapless::String<N> {
+    const POSTCARD_MAX_SIZE: usize = varint_size(N) + N;
+}
+
+#[cfg(feature = "heapless")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{to_slice, to_slice_cobs, to_slice_varint};
+    use heapless::{String, Vec};
+
+    #[test]
+    fn max_size_primitives() {
+        let mut buf = [0u8; 32];
+
+        assert_eq!(
+            to_slice_varint(&u16::MAX, &mut buf).unwrap().len(),
+            u16::POSTCARD_MAX_SIZE
+        );
+        assert_eq!(
+            to_slice_varint(&i32::MIN, &mut buf).unwrap().len(),
+            i32::POSTCARD_MAX_SIZE
+        );
+        assert_eq!(
+            to_slice_varint(&u128::MAX, &mut buf).unwrap().len(),
+            u128::POSTCARD_MAX_SIZE
+        );
+        assert_eq!(
+            to_slice(&usize::MAX, &mut buf).unwrap().len(),
+            <usize>::POSTCARD_MAX_SIZE - 2
+        );
+        assert_eq!(
+            to_slice(&'\u{10FFFF}', &mut buf).unwrap().len(),
+            char::POSTCARD_MAX_SIZE
+        );
+        assert_eq!(
+            to_slice(&Some((1u8, 2.0f64)), &mut buf).unwrap().len(),
+            <Option<(u8, f64)>>::POSTCARD_MAX_SIZE
+        );
+    }
+
+    #[test]
+    fn max_size_heapless() {
+        let mut buf = [0u8; 512];
+
+        let mut vec: Vec<u8, 200> = Vec::new();
+        vec.resize(200, 0xAA).unwrap();
+        assert_eq!(
+            to_slice(&vec, &mut buf).unwrap().len(),
+            <Vec<u8, 200>>::POSTCARD_MAX_SIZE
+        );
+
+        let mut string: String<100> = String::new();
+        (0..100).for_each(|_| string.push('a').unwrap());
+        assert_eq!(
+            to_slice(&string, &mut buf).unwrap().len(),
+            <String<100>>::POSTCARD_MAX_SIZE
+        );
+
+        // Exactly enough room for the worst case COBS overhead
+        let mut vec: Vec<u8, 300> = Vec::new();
+        vec.resize(300, 0xAA).unwrap();
+        let mut buf = [0u8; max_size_cobs(<Vec<u8, 300>>::POSTCARD_MAX_SIZE)];
+        assert_eq!(to_slice_cobs(&vec, &mut buf).unwrap().len(), buf.len());
+    }
+}
@@ -0,0 +1,525 @@
+//! # Schemas
+//!
+//! Postcard messages are not self-describing: the bytes on the wire do not say which types
+//! they contain. This module contains the [`Schema`] trait, which describes the layout of a type
+//! at runtime as a tree of [`NamedType`]s, using the same data model as `serde`.
+//!
+//! This allows tools which do not have access to the original Rust types (such as host-side
+//! loggers or debuggers) to understand a message layout. As the schema types are `Serialize`,
+//! a schema may itself be sent using postcard, and (with the `alloc` feature) received as an
+//! `OwnedNamedType`.
+//!
+//! With the (non-default) `derive` feature, `Schema` can be derived for structs and enums
+//! whose fields all implement `Schema`. Note that `serde` attributes, such as `#[serde(rename)]`
+//! or `#[serde(skip)]`, are not taken into account by the derive macro.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! use postcard::{
+//!     schema::{NamedValue, SdmTy},
+//!     Schema,
+//! };
+//!
+//! #[derive(Schema)]
+//! struct Reading {
+//!     sensor: u8,
+//!     value: Option<i32>,
+//! }
+//!
+//! let schema = Reading::SCHEMA;
+//! assert_eq!(schema.name, "Reading");
+//! assert_eq!(
+//!     schema.ty,
+//!     &SdmTy::Struct(&[
+//!         &NamedValue { name: "sensor", ty: u8::SCHEMA },
+//!         &NamedValue { name: "value", ty: <Option<i32>>::SCHEMA },
+//!     ]),
+//! );
+//! # }
+//! ```
+//!
+//! Integer types are described by their Rust type. How they appear on the wire depends
+//! on the [`IntEncoding`](../enum.IntEncoding.html) that was used for the message.
+
+use core::marker::PhantomData;
+use serde::Serialize;
+
+#[cfg(feature = "alloc")]
+pub use owned::*;
+
+/// A type which can describe its postcard wire layout at runtime
+pub trait Schema {
+    /// A recursive data structure that describes the schema of the given type
+    const SCHEMA: &'static NamedType;
+}
+
+/// A data type with a name, e.g. a field of a struct
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct NamedValue {
+    /// The name of this value
+    pub name: &'static str,
+    /// The type of this value
+    pub ty: &'static NamedType,
+}
+
+/// A data type, with its name
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct NamedType {
+    /// The name of this type
+    pub name: &'static str,
+    /// The type
+    pub ty: &'static SdmTy,
+}
+
+/// An enum variant with a name, e.g. `T::Bar(...)`
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct NamedVariant {
+    /// The name of this variant
+    pub name: &'static str,
+    /// The type of this variant
+    pub ty: &'static SdmTy,
+}
+
+/// Serde Data Model Types (and friends)
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub enum SdmTy {
+    /// The `bool` Serde Data Model Type
+    Bool,
+    /// The `i8` Serde Data Model Type
+    I8,
+    /// The `u8` Serde Data Model Type
+    U8,
+    /// The `i16` Serde Data Model Type
+    I16,
+    /// The `u16` Serde Data Model Type
+    U16,
+    /// The `i32` Serde Data Model Type
+    I32,
+    /// The `u32` Serde Data Model Type
+    U32,
+    /// The `i64` Serde Data Model Type
+    I64,
+    /// The `u64` Serde Data Model Type
+    U64,
+    /// The `i128` Serde Data Model Type
+    I128,
+    /// The `u128` Serde Data Model Type
+    U128,
+    /// The `isize` type, which is sent as an `i64`
+    Isize,
+    /// The `usize` type, which is sent as a `u64`
+    Usize,
+    /// The `f32` Serde Data Model Type
+    F32,
+    /// The `f64` Serde Data Model Type
+    F64,
+    /// The `char` Serde Data Model Type
+    Char,
+    /// The `String` Serde Data Model Type
+    String,
+    /// The `&[u8]` Serde Data Model Type
+    ByteArray,
+    /// The `Option<T>` Serde Data Model Type
+    Option(&'static NamedType),
+    /// The `()` Serde Data Model Type
+    Unit,
+    /// The "unit struct" Serde Data Model Type
+    UnitStruct,
+    /// The "unit variant" Serde Data Model Type
+    UnitVariant,
+    /// The "newtype struct" Serde Data Model Type
+    NewtypeStruct(&'static NamedType),
+    /// The "newtype variant" Serde Data Model Type
+    NewtypeVariant(&'static NamedType),
+    /// The "Sequence" Serde Data Model Type
+    Seq(&'static NamedType),
+    /// The "Tuple" Serde Data Model Type
+    Tuple(&'static [&'static NamedType]),
+    /// The "Tuple Struct" Serde Data Model Type
+    TupleStruct(&'static [&'static NamedType]),
+    /// The "Tuple Variant" Serde Data Model Type
+    TupleVariant(&'static [&'static NamedType]),
+    /// The "Map" Serde Data Model Type
+    Map {
+        /// The map "Key" type
+        key: &'static NamedType,
+        /// The map "Value" type
+        val: &'static NamedType,
+    },
+    /// The "Struct" Serde Data Model Type
+    Struct(&'static [&'static NamedValue]),
+    /// The "Struct Variant" Serde Data Model Type
+    StructVariant(&'static [&'static NamedValue]),
+    /// The "Enum" Serde Data Model Type (which contains any of the "Variant" types)
+    Enum(&'static [&'static NamedVariant]),
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty => $sdm:expr),* $(,)?) => {
+        $(
+            impl Schema for $ty {
+                const SCHEMA: &'static NamedType = &NamedType {
+                    name: stringify!($ty),
+                    ty: &$sdm,
+                };
+            }
+        )*
+    };
+}
+
+impl_schema! {
+    () => SdmTy::Unit,
+    bool => SdmTy::Bool,
+    i8 => SdmTy::I8,
+    u8 => SdmTy::U8,
+    i16 => SdmTy::I16,
+    u16 => SdmTy::U16,
+    i32 => SdmTy::I32,
+    u32 => SdmTy::U32,
+    i64 => SdmTy::I64,
+    u64 => SdmTy::U64,
+    i128 => SdmTy::I128,
+    u128 => SdmTy::U128,
+    isize => SdmTy::Isize,
+    usize => SdmTy::Usize,
+    f32 => SdmTy::F32,
+    f64 => SdmTy::F64,
+    char => SdmTy::Char,
+    str => SdmTy::String,
+}
+
+impl<T: Schema + ?Sized> Schema for &'_ T {
+    const SCHEMA: &'static NamedType = T::SCHEMA;
+}
+
+impl<T: Schema + ?Sized> Schema for &'_ mut T {
+    const SCHEMA: &'static NamedType = T::SCHEMA;
+}
+
+impl<T: Schema> Schema for [T] {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "[T]",
+        ty: &SdmTy::Seq(T::SCHEMA),
+    };
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "[T; N]",
+        ty: &SdmTy::Tuple(&[T::SCHEMA; N]),
+    };
+}
+
+impl<T: Schema> Schema for Option<T> {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "Option<T>",
+        ty: &SdmTy::Option(T::SCHEMA),
+    };
+}
+
+impl<T: Schema, E: Schema> Schema for Result<T, E> {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "Result<T, E>",
+        ty: &SdmTy::Enum(&[
+            &NamedVariant {
+                name: "Ok",
+                ty: &SdmTy::NewtypeVariant(T::SCHEMA),
+            },
+            &NamedVariant {
+                name: "Err",
+                ty: &SdmTy::NewtypeVariant(E::SCHEMA),
+            },
+        ]),
+    };
+}
+
+impl<T: ?Sized> Schema for PhantomData<T> {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "PhantomData",
+        ty: &SdmTy::UnitStruct,
+    };
+}
+
+macro_rules! impl_schema_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: Schema),+> Schema for ($($ty,)+) {
+            const SCHEMA: &'static NamedType = &NamedType {
+                name: stringify!(($($ty,)+)),
+                ty: &SdmTy::Tuple(&[$($ty::SCHEMA),+]),
+            };
+        }
+    };
+}
+
+impl_schema_tuple!(A);
+impl_schema_tuple!(A, B);
+impl_schema_tuple!(A, B, C);
+impl_schema_tuple!(A, B, C, D);
+impl_schema_tuple!(A, B, C, D, E);
+impl_schema_tuple!(A, B, C, D, E, F);
+impl_schema_tuple!(A, B, C, D, E, F, G);
+impl_schema_tuple!(A, B, C, D, E, F, G, H);
+impl_schema_tuple!(A, B, C, D, E, F, G, H, I);
+impl_schema_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_schema_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_schema_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(feature = "heapless")]
+impl<T: Schema, const N: usize> Schema for heapless::Vec<T, N> {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "heapless::Vec<T, N>",
+        ty: &SdmTy::Seq(T::SCHEMA),
+    };
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> Schema for heapless::String<N> {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "heapless::String<N>",
+        ty: &SdmTy::String,
+    };
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    extern crate alloc;
+    use super::{NamedType, Schema, SdmTy};
+    use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+
+    impl<T: Schema> Schema for Vec<T> {
+        const SCHEMA: &'static NamedType = &NamedType {
+            name: "Vec<T>",
+            ty: &SdmTy::Seq(T::SCHEMA),
+        };
+    }
+
+    impl Schema for String {
+        const SCHEMA: &'static NamedType = &NamedType {
+            name: "String",
+            ty: &SdmTy::String,
+        };
+    }
+
+    impl<T: Schema + ?Sized> Schema for Box<T> {
+        const SCHEMA: &'static NamedType = T::SCHEMA;
+    }
+
+    impl<K: Schema, V: Schema> Schema for BTreeMap<K, V> {
+        const SCHEMA: &'static NamedType = &NamedType {
+            name: "BTreeMap<K, V>",
+            ty: &SdmTy::Map {
+                key: K::SCHEMA,
+                val: V::SCHEMA,
+            },
+        };
+    }
+}
+
+#[cfg(feature = "use-std")]
+impl<K: Schema, V: Schema, S> Schema for std::collections::HashMap<K, V, S> {
+    const SCHEMA: &'static NamedType = &NamedType {
+        name: "HashMap<K, V>",
+        ty: &SdmTy::Map {
+            key: K::SCHEMA,
+            val: V::SCHEMA,
+        },
+    };
+}
+
+#[cfg(feature = "alloc")]
+mod owned {
+    extern crate alloc;
+    use super::{NamedType, NamedValue, NamedVariant, SdmTy};
+    use alloc::{
+        boxed::Box,
+        string::{String, ToString},
+        vec::Vec,
+    };
+    use serde::{Deserialize, Serialize};
+
+    /// The owned counterpart of [`NamedValue`], e.g. for schemas received at runtime.
+    ///
+    /// This type is only available when the (non-default) `alloc` feature is active
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct OwnedNamedValue {
+        /// The name of this value
+        pub name: String,
+        /// The type of this value
+        pub ty: OwnedNamedType,
+    }
+
+    /// The owned counterpart of [`NamedType`], e.g. for schemas received at runtime.
+    ///
+    /// A [`NamedType`] and an `OwnedNamedType` have the same serialized representation.
+    ///
+    /// This type is only available when the (non-default) `alloc` feature is active
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct OwnedNamedType {
+        /// The name of this type
+        pub name: String,
+        /// The type
+        pub ty: OwnedSdmTy,
+    }
+
+    /// The owned counterpart of [`NamedVariant`], e.g. for schemas received at runtime.
+    ///
+    /// This type is only available when the (non-default) `alloc` feature is active
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct OwnedNamedVariant {
+        /// The name of this variant
+        pub name: String,
+        /// The type of this variant
+        pub ty: OwnedSdmTy,
+    }
+
+    /// The owned counterpart of [`SdmTy`], e.g. for schemas received at runtime.
+    ///
+    /// This type is only available when the (non-default) `alloc` feature is active
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    #[allow(missing_docs)]
+    pub enum OwnedSdmTy {
+        Bool,
+        I8,
+        U8,
+        I16,
+        U16,
+        I32,
+        U32,
+        I64,
+        U64,
+        I128,
+        U128,
+        Isize,
+        Usize,
+        F32,
+        F64,
+        Char,
+        String,
+        ByteArray,
+        Option(Box<OwnedNamedType>),
+        Unit,
+        UnitStruct,
+        UnitVariant,
+        NewtypeStruct(Box<OwnedNamedType>),
+        NewtypeVariant(Box<OwnedNamedType>),
+        Seq(Box<OwnedNamedType>),
+        Tuple(Vec<OwnedNamedType>),
+        TupleStruct(Vec<OwnedNamedType>),
+        TupleVariant(Vec<OwnedNamedType>),
+        Map {
+            key: Box<OwnedNamedType>,
+            val: Box<OwnedNamedType>,
+        },
+        Struct(Vec<OwnedNamedValue>),
+        StructVariant(Vec<OwnedNamedValue>),
+        Enum(Vec<OwnedNamedVariant>),
+    }
+
+    impl From<&NamedValue> for OwnedNamedValue {
+        fn from(value: &NamedValue) -> Self {
+            Self {
+                name: value.name.to_string(),
+                ty: value.ty.into(),
+            }
+        }
+    }
+
+    impl From<&NamedType> for OwnedNamedType {
+        fn from(value: &NamedType) -> Self {
+            Self {
+                name: value.name.to_string(),
+                ty: value.ty.into(),
+            }
+        }
+    }
+
+    impl From<&NamedVariant> for OwnedNamedVariant {
+        fn from(value: &NamedVariant) -> Self {
+            Self {
+                name: value.name.to_string(),
+                ty: value.ty.into(),
+            }
+        }
+    }
+
+    impl From<&SdmTy> for OwnedSdmTy {
+        fn from(other: &SdmTy) -> Self {
+            fn boxed(nt: &NamedType) -> Box<OwnedNamedType> {
+                Box::new(nt.into())
+            }
+
+            match *other {
+                SdmTy::Bool => Self::Bool,
+                SdmTy::I8 => Self::I8,
+                SdmTy::U8 => Self::U8,
+                SdmTy::I16 => Self::I16,
+                SdmTy::U16 => Self::U16,
+                SdmTy::I32 => Self::I32,
+                SdmTy::U32 => Self::U32,
+                SdmTy::I64 => Self::I64,
+                SdmTy::U64 => Self::U64,
+                SdmTy::I128 => Self::I128,
+                SdmTy::U128 => Self::U128,
+                SdmTy::Isize => Self::Isize,
+                SdmTy::Usize => Self::Usize,
+                SdmTy::F32 => Self::F32,
+                SdmTy::F64 => Self::F64,
+                SdmTy::Char => Self::Char,
+                SdmTy::String => Self::String,
+                SdmTy::ByteArray => Self::ByteArray,
+                SdmTy::Option(nt) => Self::Option(boxed(nt)),
+                SdmTy::Unit => Self::Unit,
+                SdmTy::UnitStruct => Self::UnitStruct,
+                SdmTy::UnitVariant => Self::UnitVariant,
+                SdmTy::NewtypeStruct(nt) => Self::NewtypeStruct(boxed(nt)),
+                SdmTy::NewtypeVariant(nt) => Self::NewtypeVariant(boxed(nt)),
+                SdmTy::Seq(nt) => Self::Seq(boxed(nt)),
+                SdmTy::Tuple(nts) => Self::Tuple(nts.iter().map(|nt| (*nt).into()).collect()),
+                SdmTy::TupleStruct(nts) => {
+                    Self::TupleStruct(nts.iter().map(|nt| (*nt).into()).collect())
+                }
+                SdmTy::TupleVariant(nts) => {
+                    Self::TupleVariant(nts.iter().map(|nt| (*nt).into()).collect())
+                }
+                SdmTy::Map { key, val } => Self::Map {
+                    key: boxed(key),
+                    val: boxed(val),
+                },
+                SdmTy::Struct(nvs) => Self::Struct(nvs.iter().map(|nv| (*nv).into()).collect()),
+                SdmTy::StructVariant(nvs) => {
+                    Self::StructVariant(nvs.iter().map(|nv| (*nv).into()).collect())
+                }
+                SdmTy::Enum(nvs) => Self::Enum(nvs.iter().map(|nv| (*nv).into()).collect()),
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "heapless"))]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{from_bytes, to_allocvec};
+
+    #[test]
+    fn owned_schema_roundtrip() {
+        let schema = <Result<(u8, [i16; 2]), heapless::Vec<char, 4>>>::SCHEMA;
+        let owned = OwnedNamedType::from(schema);
+
+        assert_eq!(owned.name, "Result<T, E>");
+        match &owned.ty {
+            OwnedSdmTy::Enum(variants) => {
+                assert_eq!(variants.len(), 2);
+                assert_eq!(variants[1].name, "Err");
+            }
+            other => panic!("unexpected type {:?}", other),
+        }
+
+        // A static schema can be received as an owned schema
+        let bytes = to_allocvec(schema).unwrap();
+        assert_eq!(bytes, to_allocvec(&owned).unwrap());
+        assert_eq!(from_bytes::<OwnedNamedType>(&bytes).unwrap(), owned);
+    }
+}
@@ -72,8 +72,11 @@
 //! ```
 
 use crate::error::{Error, Result};
-use crate::varint::VarintUsize;
+use crate::hdlc;
+use crate::slip;
+use crate::varint::{varint_max, varint_u32_padded, VarintUsize};
 use cobs::{EncoderState, PushResult};
+use core::convert::TryFrom;
 use core::ops::Index;
 use core::ops::IndexMut;
 
@@ -86,9 +89,22 @@ pub use std_vec::*;
 #[cfg(feature = "alloc")]
 pub use alloc_vec::*;
 
+#[cfg(feature = "use-std")]
+pub use io_write::*;
+
+#[cfg(feature = "embedded-io")]
+pub use eio_write::*;
+
+#[cfg(feature = "bytes")]
+pub use buf_mut::*;
+
+#[cfg(feature = "use-crc")]
+pub use crc_flavor::*;
+
 /// The SerFlavor trait acts as a combinator/middleware interface that can be used to pass bytes
 /// through storage or modification flavors. See the module level documentation for more information
 /// and examples.
+#[allow(clippy::result_unit_err)]
 pub trait SerFlavor {
     /// The `Output` type is what this flavor "resolves" to when the serialization is complete.
     /// For storage flavors, this is typically a concrete type. For modification flavors, this is
@@ -116,6 +132,25 @@ pub trait SerFlavor {
         self.try_extend(used_buf)
     }
 
+    /// The position() trait method returns the number of bytes stored so far, if the flavor
+    /// supports overwriting them with try_patch(). This is used to fill in the length of
+    /// sequences and maps once it is known. The default implementation returns `None`.
+    ///
+    /// Of the flavors in this crate, `Slice`, `HVec`, `StdVec`, `AllocVec`, `Size` and
+    /// `LengthPrefix` support patching. The `Cobs`, `Slip`, `Hdlc` and `Crc` flavors do not,
+    /// as the bytes they have passed on depend on the bytes that would be patched. Through
+    /// these, sequences and maps of unknown length are buffered with the `alloc` feature,
+    /// and fail with `Error::SerializeSeqLengthUnknown` without it.
+    fn position(&self) -> Option<usize> {
+        None
+    }
+
+    /// The try_patch() trait method overwrites bytes that have already been stored, starting at
+    /// a position previously returned by position(). The default implementation returns an error.
+    fn try_patch(&mut self, _pos: usize, _data: &[u8]) -> core::result::Result<(), ()> {
+        Err(())
+    }
+
     /// The release() trait method finalizes the modification or storage operation, and resolved into
     /// the type defined by `SerFlavor::Output` associated type.
     fn release(self) -> core::result::Result<Self::Output, ()>;
@@ -171,6 +206,18 @@ impl<'a> SerFlavor for Slice<'a> {
         Ok(())
     }
 
+    fn position(&self) -> Option<usize> {
+        Some(self.idx)
+    }
+
+    fn try_patch(&mut self, pos: usize, data: &[u8]) -> core::result::Result<(), ()> {
+        self.buf[..self.idx]
+            .get_mut(pos..pos + data.len())
+            .ok_or(())?
+            .copy_from_slice(data);
+        Ok(())
+    }
+
     fn release(self) -> core::result::Result<Self::Output, ()> {
         let (used, _unused) = self.buf.split_at_mut(self.idx);
         Ok(used)
@@ -191,12 +238,90 @@ impl<'a> IndexMut<usize> for Slice<'a> {
     }
 }
 
+////////////////////////////////////////
+// Size
+////////////////////////////////////////
+
+/// The `Size` flavor is a storage flavor, which does not store the serialized bytes at all,
+/// but only counts them. The `Size` flavor resolves into the number of bytes that would have
+/// been stored by any other storage flavor.
+///
+/// The `Size` flavor may be used with modification flavors, such as [`Cobs`], to calculate
+/// the size of the modified output.
+///
+/// ```rust
+/// use postcard::{
+///     serialize_with_flavor,
+///     flavors::{Cobs, Size},
+/// };
+///
+/// let data: &[u8] = &[0x01, 0x00, 0x20, 0x30];
+/// let size = serialize_with_flavor::<[u8], Cobs<Size>, usize>(
+///     data,
+///     Cobs::try_new(Size::default()).unwrap(),
+/// ).unwrap();
+///
+/// assert_eq!(size, 7);
+/// ```
+#[derive(Default)]
+pub struct Size {
+    size: usize,
+    // Modification flavors may overwrite stored bytes, which are discarded here
+    scratch: u8,
+}
+
+impl SerFlavor for Size {
+    type Output = usize;
+
+    #[inline(always)]
+    fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+        self.size += data.len();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn try_push(&mut self, _data: u8) -> core::result::Result<(), ()> {
+        self.size += 1;
+        Ok(())
+    }
+
+    fn position(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn try_patch(&mut self, pos: usize, data: &[u8]) -> core::result::Result<(), ()> {
+        if pos + data.len() <= self.size {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn release(self) -> core::result::Result<Self::Output, ()> {
+        Ok(self.size)
+    }
+}
+
+impl Index<usize> for Size {
+    type Output = u8;
+
+    fn index(&self, _idx: usize) -> &u8 {
+        &self.scratch
+    }
+}
+
+impl IndexMut<usize> for Size {
+    fn index_mut(&mut self, _idx: usize) -> &mut u8 {
+        &mut self.scratch
+    }
+}
+
 #[cfg(feature = "heapless")]
 mod heapless_vec {
-    use heapless::Vec;
-    use super::SerFlavor;
     use super::Index;
     use super::IndexMut;
+    use super::SerFlavor;
+    use heapless::Vec;
 
     ////////////////////////////////////////
     // HVec
@@ -206,7 +331,7 @@ mod heapless_vec {
     /// allocated data structure, with a fixed maximum size and variable amount of contents.
     pub struct HVec<const B: usize>(Vec<u8, B>);
 
-    impl<'a, const B: usize> SerFlavor for HVec<B> {
+    impl<const B: usize> SerFlavor for HVec<B> {
         type Output = Vec<u8, B>;
 
         #[inline(always)]
@@ -219,6 +344,18 @@ mod heapless_vec {
             self.0.push(data).map_err(|_| ())
         }
 
+        fn position(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+
+        fn try_patch(&mut self, pos: usize, data: &[u8]) -> core::result::Result<(), ()> {
+            self.0
+                .get_mut(pos..pos + data.len())
+                .ok_or(())?
+                .copy_from_slice(data);
+            Ok(())
+        }
+
         fn release(self) -> core::result::Result<Vec<u8, B>, ()> {
             Ok(self.0)
         }
@@ -248,10 +385,10 @@ mod heapless_vec {
 #[cfg(feature = "use-std")]
 mod std_vec {
     extern crate std;
-    use std::vec::Vec;
-    use super::SerFlavor;
     use super::Index;
     use super::IndexMut;
+    use super::SerFlavor;
+    use std::vec::Vec;
 
     /// The `StdVec` flavor is a wrapper type around a `std::vec::Vec`.
     ///
@@ -273,6 +410,18 @@ mod std_vec {
             Ok(())
         }
 
+        fn position(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+
+        fn try_patch(&mut self, pos: usize, data: &[u8]) -> core::result::Result<(), ()> {
+            self.0
+                .get_mut(pos..pos + data.len())
+                .ok_or(())?
+                .copy_from_slice(data);
+            Ok(())
+        }
+
         fn release(self) -> core::result::Result<Self::Output, ()> {
             Ok(self.0)
         }
@@ -296,10 +445,10 @@ mod std_vec {
 #[cfg(feature = "alloc")]
 mod alloc_vec {
     extern crate alloc;
-    use alloc::vec::Vec;
-    use super::SerFlavor;
     use super::Index;
     use super::IndexMut;
+    use super::SerFlavor;
+    use alloc::vec::Vec;
 
     /// The `AllocVec` flavor is a wrapper type around an `alloc::vec::Vec`.
     ///
@@ -321,6 +470,18 @@ mod alloc_vec {
             Ok(())
         }
 
+        fn position(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+
+        fn try_patch(&mut self, pos: usize, data: &[u8]) -> core::result::Result<(), ()> {
+            self.0
+                .get_mut(pos..pos + data.len())
+                .ok_or(())?
+                .copy_from_slice(data);
+            Ok(())
+        }
+
         fn release(self) -> core::result::Result<Self::Output, ()> {
             Ok(self.0)
         }
@@ -341,6 +502,285 @@ mod alloc_vec {
     }
 }
 
+/// A writer which the bytes of the `IoWrite` and `EioWrite` flavors are passed on to
+#[cfg(any(feature = "use-std", feature = "embedded-io"))]
+trait Sink {
+    type Error;
+
+    fn write_all(&mut self, data: &[u8]) -> core::result::Result<(), Self::Error>;
+
+    fn flush(&mut self) -> core::result::Result<(), Self::Error>;
+}
+
+/// The number of bytes collected by the `IoWrite` and `EioWrite` flavors before they are
+/// passed on to the writer
+#[cfg(any(feature = "use-std", feature = "embedded-io"))]
+const WRITE_BUF_SIZE: usize = 64;
+
+/// The implementation shared by the flavors which pass bytes on to a writer. Bytes are
+/// collected in a small buffer, so that the writer is not called for every single byte, and
+/// the error which made serialization fail is kept.
+#[cfg(any(feature = "use-std", feature = "embedded-io"))]
+struct Writer<S: Sink> {
+    sink: S,
+    error: Option<S::Error>,
+    buf: [u8; WRITE_BUF_SIZE],
+    len: usize,
+}
+
+#[cfg(any(feature = "use-std", feature = "embedded-io"))]
+impl<S: Sink> Writer<S> {
+    fn new(sink: S) -> Self {
+        Self {
+            sink,
+            error: None,
+            buf: [0u8; WRITE_BUF_SIZE],
+            len: 0,
+        }
+    }
+
+    fn take_error(&mut self) -> Option<S::Error> {
+        self.error.take()
+    }
+
+    /// Pass `data` on to the writer, keeping the error if it fails
+    fn write(
+        sink: &mut S,
+        error: &mut Option<S::Error>,
+        data: &[u8],
+    ) -> core::result::Result<(), ()> {
+        sink.write_all(data).map_err(|e| {
+            *error = Some(e);
+        })
+    }
+
+    /// Pass the buffered bytes on to the writer
+    fn write_buffered(&mut self) -> core::result::Result<(), ()> {
+        let len = core::mem::take(&mut self.len);
+        Self::write(&mut self.sink, &mut self.error, &self.buf[..len])
+    }
+
+    #[inline(always)]
+    fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+        if data.len() > WRITE_BUF_SIZE - self.len {
+            self.write_buffered()?;
+            if data.len() > WRITE_BUF_SIZE {
+                return Self::write(&mut self.sink, &mut self.error, data);
+            }
+        }
+        self.buf[self.len..][..data.len()].copy_from_slice(data);
+        self.len += data.len();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+        if self.len == WRITE_BUF_SIZE {
+            self.write_buffered()?;
+        }
+        self.buf[self.len] = data;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn release(mut self) -> core::result::Result<S, ()> {
+        self.write_buffered()?;
+        self.sink.flush().map_err(|_| ())?;
+        Ok(self.sink)
+    }
+}
+
+#[cfg(feature = "use-std")]
+mod io_write {
+    extern crate std;
+    use super::{SerFlavor, Sink, Writer};
+    use std::io::{self, Write};
+
+    /// The `IoWrite` flavor is a storage flavor, which passes the serialized bytes on to a
+    /// [`std::io::Write`] implementation, such as a file or socket, rather than buffering the
+    /// whole message in memory. Bytes are collected in a small internal buffer and written in
+    /// chunks of up to 64 bytes, so an unbuffered writer such as a `File` or `TcpStream` does
+    /// not need to be wrapped in a [`std::io::BufWriter`]. The remaining bytes are written and
+    /// the writer is flushed when the flavor is released, and the `IoWrite` flavor resolves
+    /// into the writer.
+    ///
+    /// If the writer returns an error, it is kept by the flavor, and may be retrieved with
+    /// [`IoWrite::take_error()`]. Note that the `IoWrite` flavor can not be used inside of
+    /// the `Cobs` flavor, as bytes can not be modified once they have been written.
+    ///
+    /// This type is only available when the (non-default) `use-std` feature is active
+    pub struct IoWrite<W: Write> {
+        writer: Writer<StdSink<W>>,
+    }
+
+    struct StdSink<W>(W);
+
+    impl<W: Write> Sink for StdSink<W> {
+        type Error = io::Error;
+
+        fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+            self.0.write_all(data)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl<W: Write> IoWrite<W> {
+        /// Create a new `IoWrite` flavor from a given writer
+        pub fn new(writer: W) -> Self {
+            Self {
+                writer: Writer::new(StdSink(writer)),
+            }
+        }
+
+        /// Take the I/O error that caused serialization to fail, if any
+        pub fn take_error(&mut self) -> Option<io::Error> {
+            self.writer.take_error()
+        }
+    }
+
+    impl<W: Write> SerFlavor for IoWrite<W> {
+        type Output = W;
+
+        #[inline(always)]
+        fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+            self.writer.try_extend(data)
+        }
+
+        #[inline(always)]
+        fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+            self.writer.try_push(data)
+        }
+
+        fn release(self) -> core::result::Result<Self::Output, ()> {
+            Ok(self.writer.release()?.0)
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+mod eio_write {
+    use super::{SerFlavor, Sink, Writer};
+    use embedded_io::Write;
+
+    /// The `EioWrite` flavor is a storage flavor for `no_std` environments, which passes the
+    /// serialized bytes on to an [`embedded_io::Write`] implementation, such as a UART driver,
+    /// as they are produced. No buffer for the message is needed, the bytes are handed to the
+    /// driver in chunks of up to 64 bytes. The writer is flushed when the flavor is released,
+    /// and the `EioWrite` flavor resolves into the writer.
+    ///
+    /// A driver error stops serialization, and may be retrieved with
+    /// [`EioWrite::take_error()`]. Bytes written before the error have already been sent, so
+    /// the receiver will see a truncated message.
+    ///
+    /// This type is only available when the (non-default) `embedded-io` feature is active
+    pub struct EioWrite<W: Write> {
+        writer: Writer<EioSink<W>>,
+    }
+
+    struct EioSink<W>(W);
+
+    impl<W: Write> Sink for EioSink<W> {
+        type Error = W::Error;
+
+        fn write_all(&mut self, data: &[u8]) -> Result<(), W::Error> {
+            self.0.write_all(data)
+        }
+
+        fn flush(&mut self) -> Result<(), W::Error> {
+            self.0.flush()
+        }
+    }
+
+    impl<W: Write> EioWrite<W> {
+        /// Create a new `EioWrite` flavor from a given writer
+        pub fn new(writer: W) -> Self {
+            Self {
+                writer: Writer::new(EioSink(writer)),
+            }
+        }
+
+        /// Take the I/O error that caused serialization to fail, if any
+        pub fn take_error(&mut self) -> Option<W::Error> {
+            self.writer.take_error()
+        }
+    }
+
+    impl<W: Write> SerFlavor for EioWrite<W> {
+        type Output = W;
+
+        #[inline(always)]
+        fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+            self.writer.try_extend(data)
+        }
+
+        #[inline(always)]
+        fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+            self.writer.try_push(data)
+        }
+
+        fn release(self) -> core::result::Result<Self::Output, ()> {
+            Ok(self.writer.release()?.0)
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod buf_mut {
+    use super::SerFlavor;
+    use bytes::BufMut;
+
+    /// The `BufMutFlavor` is a storage flavor, which puts the serialized bytes directly into a
+    /// [`bytes::BufMut`] implementation, such as a `BytesMut`, without an intermediate buffer.
+    /// The `BufMutFlavor` resolves into the `BufMut`.
+    ///
+    /// Serialization fails with [`Error::SerializeBufferFull`] if the `BufMut` has no
+    /// remaining capacity, which never happens for a `BytesMut` or `Vec<u8>`, as these grow
+    /// as needed.
+    ///
+    /// This type is only available when the (non-default) `bytes` feature is active
+    ///
+    /// [`Error::SerializeBufferFull`]: ../enum.Error.html#variant.SerializeBufferFull
+    pub struct BufMutFlavor<B: BufMut> {
+        buf: B,
+    }
+
+    impl<B: BufMut> BufMutFlavor<B> {
+        /// Create a new `BufMutFlavor` from a given `BufMut`
+        pub fn new(buf: B) -> Self {
+            Self { buf }
+        }
+    }
+
+    impl<B: BufMut> SerFlavor for BufMutFlavor<B> {
+        type Output = B;
+
+        #[inline(always)]
+        fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+            if self.buf.remaining_mut() < data.len() {
+                return Err(());
+            }
+            self.buf.put_slice(data);
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+            if !self.buf.has_remaining_mut() {
+                return Err(());
+            }
+            self.buf.put_u8(data);
+            Ok(())
+        }
+
+        fn release(self) -> core::result::Result<Self::Output, ()> {
+            Ok(self.buf)
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Modification Flavors
 ////////////////////////////////////////////////////////////////////////////////
@@ -355,6 +795,10 @@ mod alloc_vec {
 ///
 /// This protocol is useful when sending data over a serial interface without framing such as a UART
 ///
+/// The `Cobs` flavor does not support [`SerFlavor::try_patch()`], as the code bytes already
+/// written depend on where the zero bytes are. Without the `alloc` feature, sequences and maps
+/// of unknown length can therefore not be serialized with it, not even to a `Cobs<Size>`.
+///
 /// [Consistent Overhead Byte Stuffing]: https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing
 pub struct Cobs<B>
 where
@@ -379,7 +823,7 @@ where
     }
 }
 
-impl<'a, B> SerFlavor for Cobs<B>
+impl<B> SerFlavor for Cobs<B>
 where
     B: SerFlavor + IndexMut<usize, Output = u8>,
 {
@@ -409,3 +853,446 @@ where
         self.flav.release()
     }
 }
+
+////////////////////////////////////////
+// SLIP
+////////////////////////////////////////
+
+/// The `Slip` flavor implements [Serial Line Internet Protocol] (RFC 1055) framing on the
+/// serialized data. Any `0xC0` (END) and `0xDB` (ESC) bytes are escaped, and the output of
+/// this flavor includes the terminating END byte.
+///
+/// Unlike the [`Cobs`] flavor, the `Slip` flavor does not modify bytes once they have been
+/// passed on, so it may be used with any inner flavor. For the same reason, it does not
+/// support [`SerFlavor::try_patch()`]: a patched byte may need escaping where the original
+/// did not. Without the `alloc` feature, sequences and maps of unknown length can therefore
+/// not be serialized with it.
+///
+/// [Serial Line Internet Protocol]: https://datatracker.ietf.org/doc/html/rfc1055
+///
+/// ```rust
+/// use postcard::{
+///     serialize_with_flavor,
+///     flavors::{Slip, Slice},
+/// };
+///
+/// let buffer = &mut [0u8; 32];
+/// let res = serialize_with_flavor::<[u8], Slip<Slice>, &mut [u8]>(
+///     &[0x01, 0xC0, 0xDB],
+///     Slip::new(Slice::new(buffer)),
+/// ).unwrap();
+///
+/// assert_eq!(res, &[0x03, 0x01, 0xDB, 0xDC, 0xDB, 0xDD, 0xC0]);
+/// ```
+pub struct Slip<B>
+where
+    B: SerFlavor,
+{
+    flav: B,
+}
+
+impl<B> Slip<B>
+where
+    B: SerFlavor,
+{
+    /// Create a new Slip modifier Flavor
+    pub fn new(bee: B) -> Self {
+        Self { flav: bee }
+    }
+}
+
+impl<B> SerFlavor for Slip<B>
+where
+    B: SerFlavor,
+{
+    type Output = <B as SerFlavor>::Output;
+
+    #[inline(always)]
+    fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+        // Pass on runs of bytes that do not need escaping in one go
+        for chunk in data.split_inclusive(|b| *b == slip::END || *b == slip::ESC) {
+            match chunk.split_last() {
+                Some((&last, rest)) if last == slip::END || last == slip::ESC => {
+                    self.flav.try_extend(rest)?;
+                    self.try_push(last)?;
+                }
+                _ => self.flav.try_extend(chunk)?,
+            }
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+        match data {
+            slip::END => self.flav.try_extend(&[slip::ESC, slip::ESC_END]),
+            slip::ESC => self.flav.try_extend(&[slip::ESC, slip::ESC_ESC]),
+            _ => self.flav.try_push(data),
+        }
+    }
+
+    fn release(mut self) -> core::result::Result<Self::Output, ()> {
+        self.flav.try_push(slip::END)?;
+        self.flav.release()
+    }
+}
+
+////////////////////////////////////////
+// HDLC
+////////////////////////////////////////
+
+/// The `Hdlc` flavor implements HDLC-like asynchronous framing, as described in RFC 1662,
+/// on the serialized data. The output of this flavor is enclosed in `0x7E` flag bytes, and
+/// followed by a 16-bit frame check sequence (CRC-16/X-25) in little endian order. Flag and
+/// `0x7D` escape bytes in the data and frame check sequence are escaped, other control
+/// characters are sent unmodified.
+///
+/// Like the [`Slip`] flavor, the `Hdlc` flavor may be used with any inner flavor, and does
+/// not support [`SerFlavor::try_patch()`], as the frame check sequence has already taken the
+/// original bytes into account.
+///
+/// ```rust
+/// use postcard::{
+///     serialize_with_flavor,
+///     flavors::{Hdlc, Slice},
+/// };
+///
+/// let buffer = &mut [0u8; 32];
+/// let res = serialize_with_flavor::<[u8], Hdlc<Slice>, &mut [u8]>(
+///     &[0x7E, 0x7D],
+///     Hdlc::try_new(Slice::new(buffer)).unwrap(),
+/// ).unwrap();
+///
+/// assert_eq!(&res[..6], &[0x7E, 0x02, 0x7D, 0x5E, 0x7D, 0x5D]);
+/// assert_eq!(res.last(), Some(&0x7E));
+/// ```
+pub struct Hdlc<B>
+where
+    B: SerFlavor,
+{
+    flav: B,
+    fcs: hdlc::Fcs16,
+}
+
+impl<B> Hdlc<B>
+where
+    B: SerFlavor,
+{
+    /// Create a new Hdlc modifier Flavor. If there is insufficient space
+    /// to push the opening flag byte, the method will return an Error
+    pub fn try_new(mut bee: B) -> Result<Self> {
+        bee.try_push(hdlc::FLAG)
+            .map_err(|_| Error::SerializeBufferFull)?;
+        Ok(Self {
+            flav: bee,
+            fcs: hdlc::Fcs16::new(),
+        })
+    }
+
+    fn push_escaped(&mut self, data: u8) -> core::result::Result<(), ()> {
+        match data {
+            hdlc::FLAG | hdlc::ESC => self.flav.try_extend(&[hdlc::ESC, data ^ hdlc::XOR]),
+            _ => self.flav.try_push(data),
+        }
+    }
+}
+
+impl<B> SerFlavor for Hdlc<B>
+where
+    B: SerFlavor,
+{
+    type Output = <B as SerFlavor>::Output;
+
+    #[inline(always)]
+    fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+        self.fcs.update(data);
+        // Pass on runs of bytes that do not need escaping in one go
+        for chunk in data.split_inclusive(|b| *b == hdlc::FLAG || *b == hdlc::ESC) {
+            match chunk.split_last() {
+                Some((&last, rest)) if last == hdlc::FLAG || last == hdlc::ESC => {
+                    self.flav.try_extend(rest)?;
+                    self.push_escaped(last)?;
+                }
+                _ => self.flav.try_extend(chunk)?,
+            }
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+        self.fcs.update(&[data]);
+        self.push_escaped(data)
+    }
+
+    fn release(mut self) -> core::result::Result<Self::Output, ()> {
+        for byte in self.fcs.finish().to_le_bytes().iter() {
+            self.push_escaped(*byte)?;
+        }
+        self.flav.try_push(hdlc::FLAG)?;
+        self.flav.release()
+    }
+}
+
+////////////////////////////////////////
+// Length Prefix
+////////////////////////////////////////
+
+/// The format of the length prefix written by the [`LengthPrefix`] flavor.
+///
+/// All fixed width formats are little endian.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub enum PrefixFormat {
+    /// The length is encoded as a varint. As the length is not known in advance, the
+    /// [`LengthPrefix`] flavor always uses five bytes, which allows lengths of up to
+    /// `u32::MAX` bytes. Decoders also accept shorter varints.
+    Varint,
+    /// The length is encoded as a `u16`
+    U16,
+    /// The length is encoded as a `u32`
+    U32,
+}
+
+impl PrefixFormat {
+    /// The number of bytes reserved for the length prefix by the [`LengthPrefix`] flavor
+    pub const fn size(self) -> usize {
+        match self {
+            PrefixFormat::Varint => varint_max::<u32>(),
+            PrefixFormat::U16 => 2,
+            PrefixFormat::U32 => 4,
+        }
+    }
+
+    /// Attempt to decode a length prefix from the start of `buf`. Returns the size of
+    /// the prefix and the length of the frame, or `None` if more bytes are needed.
+    pub(crate) fn decode(self, buf: &[u8]) -> Result<Option<(usize, usize)>> {
+        match self {
+            PrefixFormat::Varint => {
+                let mut out = 0usize;
+                for (i, byte) in buf.iter().take(varint_max::<u32>()).enumerate() {
+                    out |= ((byte & 0x7F) as usize) << (7 * i);
+                    if (byte & 0x80) == 0 {
+                        return Ok(Some((i + 1, out)));
+                    }
+                }
+                if buf.len() >= varint_max::<u32>() {
+                    Err(Error::DeserializeBadVarint)
+                } else {
+                    Ok(None)
+                }
+            }
+            PrefixFormat::U16 => Ok(buf
+                .get(..2)
+                .map(|b| (2, u16::from_le_bytes([b[0], b[1]]) as usize))),
+            PrefixFormat::U32 => Ok(buf
+                .get(..4)
+                .map(|b| (4, u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize))),
+        }
+    }
+}
+
+/// The `LengthPrefix` flavor places the length of the serialized data in front of it, which
+/// allows splitting a reliable byte stream (such as a TCP connection) into frames, without the
+/// overhead of COBS encoding. The space for the prefix is reserved up front, and the length is
+/// written once the flavor is released. See [`PrefixFormat`] for the available formats.
+///
+/// Like the [`Cobs`] flavor, the inner flavor must be empty when the `LengthPrefix` flavor is
+/// created. Frames may be split from a stream with the
+/// [`LengthPrefixAccumulator`](../struct.LengthPrefixAccumulator.html).
+///
+/// ```rust
+/// use postcard::{
+///     serialize_with_flavor,
+///     flavors::{LengthPrefix, PrefixFormat, Slice},
+/// };
+///
+/// let buffer = &mut [0u8; 32];
+/// let res = serialize_with_flavor::<[u8], LengthPrefix<Slice>, &mut [u8]>(
+///     &[0x01, 0x00, 0x20],
+///     LengthPrefix::try_new(Slice::new(buffer), PrefixFormat::U16).unwrap(),
+/// ).unwrap();
+///
+/// assert_eq!(res, &[0x04, 0x00, 0x03, 0x01, 0x00, 0x20]);
+/// ```
+pub struct LengthPrefix<B>
+where
+    B: SerFlavor + IndexMut<usize, Output = u8>,
+{
+    flav: B,
+    format: PrefixFormat,
+    len: usize,
+}
+
+impl<B> LengthPrefix<B>
+where
+    B: SerFlavor + IndexMut<usize, Output = u8>,
+{
+    /// Create a new LengthPrefix modifier Flavor. If there is insufficient space
+    /// to reserve the length prefix, the method will return an Error
+    pub fn try_new(mut bee: B, format: PrefixFormat) -> Result<Self> {
+        bee.try_extend(&[0u8; varint_max::<u32>()][..format.size()])
+            .map_err(|_| Error::SerializeBufferFull)?;
+        Ok(Self {
+            flav: bee,
+            format,
+            len: 0,
+        })
+    }
+}
+
+impl<B> SerFlavor for LengthPrefix<B>
+where
+    B: SerFlavor + IndexMut<usize, Output = u8>,
+{
+    type Output = <B as SerFlavor>::Output;
+
+    #[inline(always)]
+    fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+        self.flav.try_extend(data)?;
+        self.len += data.len();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+        self.flav.try_push(data)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    // Bytes are stored unmodified, so patching can be passed on to the inner flavor
+    fn position(&self) -> Option<usize> {
+        self.flav.position()
+    }
+
+    fn try_patch(&mut self, pos: usize, data: &[u8]) -> core::result::Result<(), ()> {
+        self.flav.try_patch(pos, data)
+    }
+
+    fn release(mut self) -> core::result::Result<Self::Output, ()> {
+        let len = u32::try_from(self.len).map_err(|_| ())?;
+        match self.format {
+            PrefixFormat::Varint => {
+                // Always use all five bytes, setting the continuation bit on all but the last
+                let prefix: [u8; varint_max::<u32>()] = varint_u32_padded(len);
+                for (i, byte) in prefix.iter().enumerate() {
+                    self.flav[i] = *byte;
+                }
+            }
+            PrefixFormat::U16 => {
+                let len = u16::try_from(len).map_err(|_| ())?;
+                for (i, byte) in len.to_le_bytes().iter().enumerate() {
+                    self.flav[i] = *byte;
+                }
+            }
+            PrefixFormat::U32 => {
+                for (i, byte) in len.to_le_bytes().iter().enumerate() {
+                    self.flav[i] = *byte;
+                }
+            }
+        }
+        self.flav.release()
+    }
+}
+
+////////////////////////////////////////
+// CRC
+////////////////////////////////////////
+
+#[cfg(feature = "use-crc")]
+mod crc_flavor {
+    use super::SerFlavor;
+    use crate::checksum::CrcDigest;
+
+    /// The `Crc` flavor calculates a [Cyclic Redundancy Check] over all serialized bytes,
+    /// and appends the checksum to the output in little endian order when released.
+    ///
+    /// Any CRC-8, CRC-16, CRC-32 or CRC-64 algorithm from the [`crc`] crate may be used,
+    /// by passing in a [`crc::Digest`]. Messages may be verified with [`from_bytes_crc()`].
+    ///
+    /// To add a checksum to COBS encoded frames, such as UART frames, place the `Crc` flavor
+    /// *outside* of the `Cobs` flavor, i.e. `Crc<Cobs<Slice>>`. This way the checksum is
+    /// calculated over the message itself, and is then COBS encoded along with it.
+    ///
+    /// Note that the `Cobs<Crc<Slice>>` nesting does **not** work, and is rejected by the
+    /// compiler. The `Cobs` flavor patches bytes after it has passed them on, so a checksum
+    /// calculated underneath it would not match the output, and would be appended after the
+    /// terminating zero byte of the frame.
+    ///
+    /// As the checksum is updated with every byte passed through, the `Crc` flavor does not
+    /// support [`SerFlavor::try_patch()`]. Without the `alloc` feature, sequences and maps of
+    /// unknown length can therefore not be serialized with it.
+    ///
+    /// This type is only available when the (non-default) `use-crc` feature is active.
+    ///
+    /// ```rust
+    /// use crc::{Crc, CRC_16_IBM_SDLC};
+    /// use postcard::{
+    ///     serialize_with_flavor,
+    ///     flavors::{self, Cobs, Slice},
+    /// };
+    ///
+    /// const CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_SDLC);
+    ///
+    /// let buffer = &mut [0u8; 32];
+    /// let res = serialize_with_flavor::<[u8], flavors::Crc<Cobs<Slice>, _>, &mut [u8]>(
+    ///     &[0x01, 0x00, 0x20],
+    ///     flavors::Crc::new(Cobs::try_new(Slice::new(buffer)).unwrap(), CRC.digest()),
+    /// ).unwrap();
+    ///
+    /// let sz = cobs::decode_in_place(res).unwrap();
+    /// let out: &[u8] = postcard::from_bytes_crc(&res[..sz], CRC.digest()).unwrap();
+    /// assert_eq!(out, &[0x01, 0x00, 0x20]);
+    /// ```
+    ///
+    /// [Cyclic Redundancy Check]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+    /// [`crc`]: https://crates.io/crates/crc
+    /// [`crc::Digest`]: https://docs.rs/crc/latest/crc/struct.Digest.html
+    /// [`from_bytes_crc()`]: ../fn.from_bytes_crc.html
+    pub struct Crc<B, D>
+    where
+        B: SerFlavor,
+        D: CrcDigest,
+    {
+        flav: B,
+        digest: D,
+    }
+
+    impl<B, D> Crc<B, D>
+    where
+        B: SerFlavor,
+        D: CrcDigest,
+    {
+        /// Create a new Crc modifier Flavor, using the given digest
+        pub fn new(bee: B, digest: D) -> Self {
+            Self { flav: bee, digest }
+        }
+    }
+
+    impl<B, D> SerFlavor for Crc<B, D>
+    where
+        B: SerFlavor,
+        D: CrcDigest,
+    {
+        type Output = <B as SerFlavor>::Output;
+
+        #[inline(always)]
+        fn try_extend(&mut self, data: &[u8]) -> core::result::Result<(), ()> {
+            self.digest.update(data);
+            self.flav.try_extend(data)
+        }
+
+        #[inline(always)]
+        fn try_push(&mut self, data: u8) -> core::result::Result<(), ()> {
+            self.digest.update(&[data]);
+            self.flav.try_push(data)
+        }
+
+        fn release(mut self) -> core::result::Result<Self::Output, ()> {
+            let sum = self.digest.finalize().to_le_bytes();
+            self.flav.try_extend(&sum[..D::SIZE])?;
+            self.flav.release()
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use serde::Serialize;
 use crate::error::{Error, Result};
-use crate::ser::flavors::{Cobs, SerFlavor, Slice};
+use crate::ser::flavors::{Cobs, Hdlc, LengthPrefix, PrefixFormat, SerFlavor, Size, Slice, Slip};
 
 #[cfg(feature = "heapless")]
 use crate::ser::flavors::HVec;
@@ -9,7 +9,13 @@ use crate::ser::flavors::HVec;
 use heapless::Vec;
 
 #[cfg(feature = "use-std")]
-use crate::ser::flavors::StdVec;
+use crate::ser::flavors::{IoWrite, StdVec};
+
+#[cfg(feature = "embedded-io")]
+use crate::ser::flavors::EioWrite;
+
+#[cfg(feature = "bytes")]
+use crate::ser::flavors::BufMutFlavor;
 
 #[cfg(feature = "alloc")]
 use crate::ser::flavors::AllocVec;
@@ -17,7 +23,13 @@ use crate::ser::flavors::AllocVec;
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "use-crc")]
+use crate::checksum::CrcDigest;
+#[cfg(feature = "use-crc")]
+use crate::ser::flavors::Crc;
+
 use crate::ser::serializer::Serializer;
+use crate::varint::IntEncoding;
 
 pub mod flavors;
 pub(crate) mod serializer;
@@ -60,6 +72,56 @@ where
     )
 }
 
+/// Serialize a `T` to the given slice, with the resulting slice containing
+/// data in a serialized then SLIP encoded format. The terminating `0xC0` END byte is included
+/// in the output buffer.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::to_slice_slip;
+/// let mut buf = [0u8; 32];
+///
+/// let used = to_slice_slip(&false, &mut buf).unwrap();
+/// assert_eq!(used, &[0x00, 0xC0]);
+///
+/// let data: &[u8] = &[0x01u8, 0xC0, 0x20, 0xDB];
+/// let used = to_slice_slip(data, &mut buf).unwrap();
+/// assert_eq!(used, &[0x04, 0x01, 0xDB, 0xDC, 0x20, 0xDB, 0xDD, 0xC0]);
+/// ```
+pub fn to_slice_slip<'a, 'b, T>(value: &'b T, buf: &'a mut [u8]) -> Result<&'a mut [u8]>
+where
+    T: Serialize + ?Sized,
+{
+    serialize_with_flavor::<T, Slip<Slice<'a>>, &'a mut [u8]>(value, Slip::new(Slice::new(buf)))
+}
+
+/// Serialize a `T` to the given slice, with the resulting slice containing
+/// data in a serialized then HDLC framed format, as produced by the [`Hdlc`] flavor.
+/// The opening and closing `0x7E` flag bytes are included in the output buffer.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::to_slice_hdlc;
+/// let mut buf = [0u8; 32];
+///
+/// let used = to_slice_hdlc(&0x7Eu8, &mut buf).unwrap();
+/// assert_eq!(&used[..3], &[0x7E, 0x7D, 0x5E]);
+/// assert_eq!(used.last(), Some(&0x7E));
+/// ```
+///
+/// [`Hdlc`]: flavors/struct.Hdlc.html
+pub fn to_slice_hdlc<'a, 'b, T>(value: &'b T, buf: &'a mut [u8]) -> Result<&'a mut [u8]>
+where
+    T: Serialize + ?Sized,
+{
+    serialize_with_flavor::<T, Hdlc<Slice<'a>>, &'a mut [u8]>(
+        value,
+        Hdlc::try_new(Slice::new(buf))?,
+    )
+}
+
 /// Serialize a `T` to the given slice, with the resulting slice containing
 /// data in a serialized format.
 ///
@@ -96,6 +158,85 @@ where
     serialize_with_flavor::<T, Slice<'a>, &'a mut [u8]>(value, Slice::new(buf))
 }
 
+/// Calculate the number of bytes needed to serialize a `T`, without storing the
+/// serialized data anywhere.
+///
+/// To calculate the size of a message with framing or other modifications, use the
+/// [`Size`] flavor with [`serialize_with_flavor()`](fn.serialize_with_flavor.html).
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::serialized_size;
+///
+/// assert_eq!(serialized_size(&true).unwrap(), 1);
+/// assert_eq!(serialized_size("Hi!").unwrap(), 4);
+/// assert_eq!(serialized_size(&[0u8; 300][..]).unwrap(), 302);
+/// ```
+///
+/// [`Size`]: flavors/struct.Size.html
+pub fn serialized_size<T>(value: &T) -> Result<usize>
+where
+    T: Serialize + ?Sized,
+{
+    serialize_with_flavor::<T, Size, usize>(value, Size::default())
+}
+
+/// Serialize a `T` to the given slice, with the resulting slice containing
+/// data in a serialized format, preceded by its length in the given [`PrefixFormat`].
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::{flavors::PrefixFormat, to_slice_length_prefix};
+/// let mut buf = [0u8; 32];
+///
+/// let used = to_slice_length_prefix("Hi!", &mut buf, PrefixFormat::U32).unwrap();
+/// assert_eq!(used, &[0x04, 0x00, 0x00, 0x00, 0x03, b'H', b'i', b'!']);
+///
+/// let used = to_slice_length_prefix(&true, &mut buf, PrefixFormat::Varint).unwrap();
+/// assert_eq!(used, &[0x81, 0x80, 0x80, 0x80, 0x00, 0x01]);
+/// ```
+///
+/// [`PrefixFormat`]: flavors/enum.PrefixFormat.html
+pub fn to_slice_length_prefix<'a, 'b, T>(
+    value: &'b T,
+    buf: &'a mut [u8],
+    format: PrefixFormat,
+) -> Result<&'a mut [u8]>
+where
+    T: Serialize + ?Sized,
+{
+    serialize_with_flavor::<T, LengthPrefix<Slice<'a>>, &'a mut [u8]>(
+        value,
+        LengthPrefix::try_new(Slice::new(buf), format)?,
+    )
+}
+
+/// Serialize a `T` to the given slice, encoding all integers wider than a byte
+/// as varints. See [`IntEncoding::Varint`] for details.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::to_slice_varint;
+/// let mut buf = [0u8; 32];
+///
+/// let used = to_slice_varint(&1u32, &mut buf).unwrap();
+/// assert_eq!(used, &[0x01]);
+///
+/// let used = to_slice_varint(&(-1i64, 128u16), &mut buf).unwrap();
+/// assert_eq!(used, &[0x01, 0x80, 0x01]);
+/// ```
+///
+/// [`IntEncoding::Varint`]: enum.IntEncoding.html#variant.Varint
+pub fn to_slice_varint<'a, 'b, T>(value: &'b T, buf: &'a mut [u8]) -> Result<&'a mut [u8]>
+where
+    T: Serialize + ?Sized,
+{
+    serialize_with_flavor_varint::<T, Slice<'a>, &'a mut [u8]>(value, Slice::new(buf))
+}
+
 /// Serialize a `T` to a `heapless::Vec<u8>`, with the `Vec` containing
 /// data in a serialized then COBS encoded format. The terminating sentinel
 /// `0x00` byte is included in the output `Vec`. Requires the (default) `heapless` feature.
@@ -210,6 +351,261 @@ where
     )
 }
 
+/// Serialize a `T` directly into a [`std::io::Write`] implementation, such as a file or
+/// a socket, without buffering the whole message in memory. The bytes are passed on in chunks
+/// of up to 64 bytes, so the writer does not need to be wrapped in a [`std::io::BufWriter`].
+/// The writer is flushed once the message has been written, and is then returned. Requires
+/// the `use-std` feature.
+///
+/// If the writer returns an error, serialization is stopped, and
+/// [`Error::SerializeIoError`] is returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::to_io;
+///
+/// let mut buf: Vec<u8> = Vec::new();
+///
+/// to_io(&true, &mut buf).unwrap();
+/// to_io("Hi!", &mut buf).unwrap();
+/// assert_eq!(buf, &[0x01, 0x03, b'H', b'i', b'!']);
+/// ```
+///
+/// [`Error::SerializeIoError`]: enum.Error.html#variant.SerializeIoError
+#[cfg(feature = "use-std")]
+pub fn to_io<T, W>(value: &T, writer: W) -> Result<W>
+where
+    T: Serialize + ?Sized,
+    W: std::io::Write,
+{
+    let mut serializer = Serializer::new(IoWrite::new(writer));
+    let res = value.serialize(&mut serializer);
+    if serializer.output.take_error().is_some() {
+        return Err(Error::SerializeIoError);
+    }
+    res?;
+    serializer
+        .output
+        .release()
+        .map_err(|_| Error::SerializeIoError)
+}
+
+/// Serialize a `T` directly into an [`embedded_io::Write`] implementation, such as a UART
+/// driver, without buffering the whole message in memory. The writer is flushed once the
+/// message has been written, and is then returned. Requires the `embedded-io` feature.
+///
+/// If the writer returns an error, serialization is stopped, and
+/// [`Error::SerializeIoError`] is returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use postcard::to_eio;
+///
+/// let mut buf = [0u8; 8];
+/// let mut writer = &mut buf[..];
+///
+/// writer = to_eio(&true, writer).unwrap();
+/// writer = to_eio("Hi!", writer).unwrap();
+/// assert_eq!(writer.len(), 3);
+/// assert_eq!(&buf[..5], &[0x01, 0x03, b'H', b'i', b'!']);
+/// ```
+///
+/// [`Error::SerializeIoError`]: enum.Error.html#variant.SerializeIoError
+#[cfg(feature = "embedded-io")]
+pub fn to_eio<T, W>(value: &T, writer: W) -> Result<W>
+where
+    T: Serialize + ?Sized,
+    W: embedded_io::Write,
+{
+    let mut serializer = Serializer::new(EioWrite::new(writer));
+    let res = value.serialize(&mut serializer);
+    if serializer.output.take_error().is_some() {
+        return Err(Error::SerializeIoError);
+    }
+    res?;
+    serializer
+        .output
+        .release()
+        .map_err(|_| Error::SerializeIoError)
+}
+
+/// Serialize a `T` into the given buffer, and write it to an [`embedded_io_async::Write`]
+/// implementation, such as an Embassy UART driver. The writer is flushed once the message has
+/// been written, and is then returned. Requires the `embedded-io-async` feature.
+///
+/// The message is serialized up front, as serialization itself can not wait for the writer, so
+/// `buf` must be large enough to hold the serialized message. Otherwise,
+/// [`Error::SerializeBufferFull`] is returned without writing anything. If the writer returns an
+/// error, [`Error::SerializeIoError`] is returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use embassy_futures::block_on;
+/// use postcard::to_async_writer;
+///
+/// let mut out = [0u8; 8];
+/// let mut buf = [0u8; 8];
+///
+/// let writer = block_on(to_async_writer("Hi!", &mut out[..], &mut buf)).unwrap();
+/// assert_eq!(writer.len(), 4);
+/// assert_eq!(&out[..4], &[0x03, b'H', b'i', b'!']);
+/// ```
+///
+/// [`Error::SerializeBufferFull`]: enum.Error.html#variant.SerializeBufferFull
+/// [`Error::SerializeIoError`]: enum.Error.html#variant.SerializeIoError
+#[cfg(feature = "embedded-io-async")]
+pub async fn to_async_writer<T, W>(value: &T, writer: W, buf: &mut [u8]) -> Result<W>
+where
+    T: Serialize + ?Sized,
+    W: embedded_io_async::Write,
+{
+    write_async(to_slice(value, buf)?, writer).await
+}
+
+/// Serialize a `T` into the given buffer, COBS encode it, and write it to an
+/// [`embedded_io_async::Write`] implementation. The terminating sentinel `0x00` byte is
+/// written as well, so the messages can be read back with
+/// [`Accumulator::read_frame()`](crate::Accumulator::read_frame). Requires the
+/// `embedded-io-async` feature.
+///
+/// See [`to_async_writer()`] for the requirements on `buf`, and the errors returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use embassy_futures::block_on;
+/// use postcard::to_async_writer_cobs;
+///
+/// let mut out = [0u8; 8];
+/// let mut buf = [0u8; 8];
+///
+/// block_on(to_async_writer_cobs(&[0x01u8, 0x00][..], &mut out[..], &mut buf)).unwrap();
+/// assert_eq!(&out[..5], &[0x03, 0x02, 0x01, 0x01, 0x00]);
+/// ```
+#[cfg(feature = "embedded-io-async")]
+pub async fn to_async_writer_cobs<T, W>(value: &T, writer: W, buf: &mut [u8]) -> Result<W>
+where
+    T: Serialize + ?Sized,
+    W: embedded_io_async::Write,
+{
+    write_async(to_slice_cobs(value, buf)?, writer).await
+}
+
+#[cfg(feature = "embedded-io-async")]
+async fn write_async<W>(msg: &[u8], mut writer: W) -> Result<W>
+where
+    W: embedded_io_async::Write,
+{
+    writer
+        .write_all(msg)
+        .await
+        .map_err(|_| Error::SerializeIoError)?;
+    writer.flush().await.map_err(|_| Error::SerializeIoError)?;
+    Ok(writer)
+}
+
+/// Serialize a `T` into the given buffer, and write it to a [`futures_io::AsyncWrite`]
+/// implementation. The writer is flushed once the message has been written, and is then
+/// returned. Requires the `futures-io` feature.
+///
+/// This is the counterpart to [`to_async_writer()`], see its documentation for the
+/// requirements on `buf`, and the errors returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use postcard::to_futures_writer;
+///
+/// let mut buf = [0u8; 8];
+///
+/// let out = block_on(to_futures_writer("Hi!", Vec::new(), &mut buf)).unwrap();
+/// assert_eq!(out, &[0x03, b'H', b'i', b'!']);
+/// ```
+#[cfg(feature = "futures-io")]
+pub async fn to_futures_writer<T, W>(value: &T, writer: W, buf: &mut [u8]) -> Result<W>
+where
+    T: Serialize + ?Sized,
+    W: futures_io::AsyncWrite + Unpin,
+{
+    write_futures(to_slice(value, buf)?, writer).await
+}
+
+/// Serialize a `T` into the given buffer, COBS encode it, and write it to a
+/// [`futures_io::AsyncWrite`] implementation. The terminating sentinel `0x00` byte is written
+/// as well, so the messages can be read back with
+/// [`Accumulator::read_futures_frame()`](crate::Accumulator::read_futures_frame). Requires the
+/// `futures-io` feature.
+///
+/// See [`to_async_writer()`] for the requirements on `buf`, and the errors returned.
+///
+/// ## Example
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use postcard::to_futures_writer_cobs;
+///
+/// let mut buf = [0u8; 8];
+///
+/// let out = block_on(to_futures_writer_cobs(&[0x01u8, 0x00][..], Vec::new(), &mut buf)).unwrap();
+/// assert_eq!(out, &[0x03, 0x02, 0x01, 0x01, 0x00]);
+/// ```
+#[cfg(feature = "futures-io")]
+pub async fn to_futures_writer_cobs<T, W>(value: &T, writer: W, buf: &mut [u8]) -> Result<W>
+where
+    T: Serialize + ?Sized,
+    W: futures_io::AsyncWrite + Unpin,
+{
+    write_futures(to_slice_cobs(value, buf)?, writer).await
+}
+
+#[cfg(feature = "futures-io")]
+async fn write_futures<W>(mut msg: &[u8], mut writer: W) -> Result<W>
+where
+    W: futures_io::AsyncWrite + Unpin,
+{
+    use core::future::poll_fn;
+    use core::pin::Pin;
+
+    while !msg.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, msg)).await {
+            Ok(0) | Err(_) => return Err(Error::SerializeIoError),
+            Ok(n) => msg = &msg[n..],
+        }
+    }
+    poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx))
+        .await
+        .map_err(|_| Error::SerializeIoError)?;
+    Ok(writer)
+}
+
+/// Serialize a `T` into a [`bytes::BufMut`] implementation, such as a `BytesMut`, and return
+/// it. Requires the `bytes` feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use bytes::{BufMut, BytesMut};
+/// use postcard::to_bufmut;
+///
+/// let mut buf = BytesMut::new();
+/// buf.put_u8(0xAA);
+///
+/// let buf = to_bufmut("Hi!", buf).unwrap();
+/// assert_eq!(&buf[..], &[0xAA, 0x03, b'H', b'i', b'!']);
+/// ```
+#[cfg(feature = "bytes")]
+pub fn to_bufmut<T, B>(value: &T, buf: B) -> Result<B>
+where
+    T: Serialize + ?Sized,
+    B: bytes::BufMut,
+{
+    serialize_with_flavor::<T, BufMutFlavor<B>, B>(value, BufMutFlavor::new(buf))
+}
+
 /// Serialize a `T` to an `alloc::vec::Vec<u8>`. Requires the `alloc` feature.
 ///
 /// ## Example
@@ -257,6 +653,95 @@ where
     )
 }
 
+/// Serialize a `T` to the given slice, followed by a checksum calculated over the serialized
+/// data using the given [`CrcDigest`]. The checksum is stored in little endian order.
+///
+/// Requires the (non-default) `use-crc` feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use crc::{Crc, CRC_8_SMBUS};
+/// use postcard::to_slice_crc;
+///
+/// const CRC: Crc<u8> = Crc::<u8>::new(&CRC_8_SMBUS);
+///
+/// let mut buf = [0u8; 32];
+/// let used = to_slice_crc("Hi!", &mut buf, CRC.digest()).unwrap();
+/// assert_eq!(used, &[0x03, b'H', b'i', b'!', 0x42]);
+/// ```
+///
+/// [`CrcDigest`]: trait.CrcDigest.html
+#[cfg(feature = "use-crc")]
+pub fn to_slice_crc<'a, 'b, T, D>(
+    value: &'b T,
+    buf: &'a mut [u8],
+    digest: D,
+) -> Result<&'a mut [u8]>
+where
+    T: Serialize + ?Sized,
+    D: CrcDigest,
+{
+    serialize_with_flavor::<T, Crc<Slice<'a>, D>, &'a mut [u8]>(
+        value,
+        Crc::new(Slice::new(buf), digest),
+    )
+}
+
+/// Serialize a `T` to a `heapless::Vec<u8>`, followed by a checksum calculated over the
+/// serialized data using the given [`CrcDigest`]. The checksum is stored in little endian order.
+///
+/// Requires the (default) `heapless` and (non-default) `use-crc` features.
+///
+/// [`CrcDigest`]: trait.CrcDigest.html
+#[cfg(all(feature = "heapless", feature = "use-crc"))]
+pub fn to_vec_crc<T, D, const B: usize>(value: &T, digest: D) -> Result<Vec<u8, B>>
+where
+    T: Serialize + ?Sized,
+    D: CrcDigest,
+{
+    serialize_with_flavor::<T, Crc<HVec<B>, D>, Vec<u8, B>>(
+        value,
+        Crc::new(HVec::default(), digest),
+    )
+}
+
+/// Serialize a `T` to a `std::vec::Vec<u8>`, followed by a checksum calculated over the
+/// serialized data using the given [`CrcDigest`]. The checksum is stored in little endian order.
+///
+/// Requires the `use-std` and `use-crc` features.
+///
+/// [`CrcDigest`]: trait.CrcDigest.html
+#[cfg(all(feature = "use-std", feature = "use-crc"))]
+pub fn to_stdvec_crc<T, D>(value: &T, digest: D) -> Result<std::vec::Vec<u8>>
+where
+    T: Serialize + ?Sized,
+    D: CrcDigest,
+{
+    serialize_with_flavor::<T, Crc<StdVec, D>, std::vec::Vec<u8>>(
+        value,
+        Crc::new(StdVec(std::vec::Vec::new()), digest),
+    )
+}
+
+/// Serialize a `T` to an `alloc::vec::Vec<u8>`, followed by a checksum calculated over the
+/// serialized data using the given [`CrcDigest`]. The checksum is stored in little endian order.
+///
+/// Requires the `alloc` and `use-crc` features.
+///
+/// [`CrcDigest`]: trait.CrcDigest.html
+#[cfg(all(feature = "alloc", feature = "use-crc"))]
+pub fn to_allocvec_crc<T, D>(value: &T, digest: D) -> Result<alloc::vec::Vec<u8>>
+where
+    T: Serialize + ?Sized,
+    D: CrcDigest,
+{
+    serialize_with_flavor::<T, Crc<AllocVec, D>, alloc::vec::Vec<u8>>(
+        value,
+        Crc::new(AllocVec(alloc::vec::Vec::new()), digest),
+    )
+}
+
 /// `serialize_with_flavor()` has three generic parameters, `T, F, O`.
 ///
 /// * `T`: This is the type that is being serialized
@@ -288,7 +773,52 @@ where
     T: Serialize + ?Sized,
     F: SerFlavor<Output = O>,
 {
-    let mut serializer = Serializer { output: flavor };
+    serialize_with_flavor_and_encoding(value, flavor, IntEncoding::Fixed)
+}
+
+/// The same as [`serialize_with_flavor()`], however all integers wider than a byte
+/// are encoded using [`IntEncoding::Varint`].
+///
+/// Messages serialized this way must be deserialized with [`from_bytes_varint()`] or
+/// [`take_from_bytes_varint()`].
+///
+/// ```rust
+/// use postcard::{
+///     serialize_with_flavor_varint,
+///     flavors::{Cobs, Slice},
+/// };
+///
+/// let buffer = &mut [0u8; 32];
+/// let res = serialize_with_flavor_varint::<(u32, i16), Cobs<Slice>, &mut [u8]>(
+///     &(300, -2),
+///     Cobs::try_new(Slice::new(buffer)).unwrap(),
+/// ).unwrap();
+///
+/// assert_eq!(res, &[0x04, 0xAC, 0x02, 0x03, 0x00]);
+/// ```
+///
+/// [`serialize_with_flavor()`]: fn.serialize_with_flavor.html
+/// [`IntEncoding::Varint`]: enum.IntEncoding.html#variant.Varint
+/// [`from_bytes_varint()`]: fn.from_bytes_varint.html
+/// [`take_from_bytes_varint()`]: fn.take_from_bytes_varint.html
+pub fn serialize_with_flavor_varint<T, F, O>(value: &T, flavor: F) -> Result<O>
+where
+    T: Serialize + ?Sized,
+    F: SerFlavor<Output = O>,
+{
+    serialize_with_flavor_and_encoding(value, flavor, IntEncoding::Varint)
+}
+
+fn serialize_with_flavor_and_encoding<T, F, O>(
+    value: &T,
+    flavor: F,
+    int_encoding: IntEncoding,
+) -> Result<O>
+where
+    T: Serialize + ?Sized,
+    F: SerFlavor<Output = O>,
+{
+    let mut serializer = Serializer::with_int_encoding(flavor, int_encoding);
     value.serialize(&mut serializer)?;
     serializer
         .output
@@ -595,4 +1125,132 @@ mod test {
 
         assert_eq!(input, x);
     }
+
+    #[test]
+    fn collect_str() {
+        struct Hex(u32);
+
+        impl Serialize for Hex {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(&format_args!("{:#x}/{}", self.0, "ok"))
+            }
+        }
+
+        let output: Vec<u8, 16> = to_vec(&Hex(0xC0FFEE)).unwrap();
+        assert_eq!(&output[..], b"\x0B0xc0ffee/ok");
+
+        let out: &str = crate::from_bytes(&output).unwrap();
+        assert_eq!(out, "0xc0ffee/ok");
+
+        let mut buf = [0u8; 6];
+        assert_eq!(
+            to_slice(&Hex(0xC0FFEE), &mut buf).err(),
+            Some(Error::SerializeBufferFull)
+        );
+    }
+
+    #[test]
+    fn collect_str_changing() {
+        use core::cell::Cell;
+        use core::fmt::{Display, Formatter};
+
+        // Displays `lens[n]` bytes on the n-th call, or fails once they run out
+        struct Changing<'a> {
+            lens: &'a [usize],
+            calls: Cell<usize>,
+        }
+
+        impl Display for Changing<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                let call = self.calls.get();
+                self.calls.set(call + 1);
+                let len = self.lens.get(call).ok_or(core::fmt::Error)?;
+                for _ in 0..*len {
+                    f.write_str("a")?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Serialize for Changing<'_> {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        fn message(lens: &[usize]) -> crate::error::CustomMessage {
+            let value = Changing {
+                lens,
+                calls: Cell::new(0),
+            };
+            match to_vec::<_, 16>(&value) {
+                Err(Error::SerdeSerCustom(msg)) => msg,
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+
+        // Longer or shorter output on the second pass
+        let changed = "Display output changed";
+        assert!(message(&[1, 2]).as_str().starts_with(changed));
+        assert!(message(&[2, 1]).as_str().starts_with(changed));
+
+        // An error on either pass
+        let failed = "Display returned an error";
+        assert!(message(&[]).as_str().starts_with(failed));
+        assert!(message(&[1]).as_str().starts_with(failed));
+
+        let value = Changing {
+            lens: &[3, 3],
+            calls: Cell::new(0),
+        };
+        let output: Vec<u8, 16> = to_vec(&value).unwrap();
+        assert_eq!(&output[..], b"\x03aaa");
+    }
+
+    #[test]
+    fn ser_varint() {
+        let mut buf = [0u8; 32];
+
+        let used = to_slice_varint(&0x7Fu16, &mut buf).unwrap();
+        assert_eq!(used, &[0x7F]);
+
+        let used = to_slice_varint(&0x80u32, &mut buf).unwrap();
+        assert_eq!(used, &[0x80, 0x01]);
+
+        let used = to_slice_varint(&u16::MAX, &mut buf).unwrap();
+        assert_eq!(used, &[0xFF, 0xFF, 0x03]);
+
+        let used = to_slice_varint(&u32::MAX, &mut buf).unwrap();
+        assert_eq!(used, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+
+        let used = to_slice_varint(&u64::MAX, &mut buf).unwrap();
+        assert_eq!(
+            used,
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]
+        );
+
+        let used = to_slice_varint(&u128::MAX, &mut buf).unwrap();
+        assert_eq!(used.len(), 19);
+        assert_eq!(used[18], 0x03);
+
+        // zigzag: 0 -> 0, -1 -> 1, 1 -> 2, -2 -> 3, ...
+        let used = to_slice_varint(&(0i32, -1i32, 1i32, -2i32), &mut buf).unwrap();
+        assert_eq!(used, &[0x00, 0x01, 0x02, 0x03]);
+
+        let used = to_slice_varint(&i64::MIN, &mut buf).unwrap();
+        assert_eq!(
+            used,
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]
+        );
+
+        // single bytes are unaffected
+        let used = to_slice_varint(&(0xFFu8, -1i8), &mut buf).unwrap();
+        assert_eq!(used, &[0xFF, 0xFF]);
+    }
 }
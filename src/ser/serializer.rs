@@ -1,8 +1,18 @@
+use core::convert::TryFrom;
 use serde::{ser, Serialize};
 
 use crate::error::{Error, Result};
 use crate::ser::flavors::SerFlavor;
-use crate::varint::VarintUsize;
+use crate::varint::*;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use crate::ser::flavors::AllocVec;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 /// A `serde` compatible serializer, generic over "Flavors" of serializing plugins.
 ///
@@ -19,6 +29,28 @@ where
     /// This is the Flavor(s) that will be used to modify or store any bytes generated
     /// by serialization
     pub output: F,
+
+    // How integers wider than a byte are encoded, set by `with_int_encoding`
+    int_encoding: IntEncoding,
+}
+
+impl<F> Serializer<F>
+where
+    F: SerFlavor,
+{
+    /// Create a new serializer using the given flavor, with the default fixed width encoding
+    /// of integers
+    pub fn new(output: F) -> Self {
+        Serializer::with_int_encoding(output, IntEncoding::Fixed)
+    }
+
+    /// Create a new serializer using the given flavor and integer encoding
+    pub fn with_int_encoding(output: F, int_encoding: IntEncoding) -> Self {
+        Serializer {
+            output,
+            int_encoding,
+        }
+    }
 }
 
 impl<'a, F> ser::Serializer for &'a mut Serializer<F>
@@ -30,14 +62,15 @@ where
     type Error = Error;
 
     // Associated types for keeping track of additional state while serializing
-    // compound data structures like sequences and maps. In this case no
+    // compound data structures like sequences and maps. Sequences and maps need
+    // to keep track of their length, if it was not known up front. Otherwise, no
     // additional state is required beyond what is already stored in the
     // Serializer struct.
-    type SerializeSeq = Self;
+    type SerializeSeq = SeqSerializer<'a, F>;
     type SerializeTuple = Self;
     type SerializeTupleStruct = Self;
     type SerializeTupleVariant = Self;
-    type SerializeMap = Self;
+    type SerializeMap = SeqSerializer<'a, F>;
     type SerializeStruct = Self;
     type SerializeStructVariant = Self;
 
@@ -54,27 +87,48 @@ where
     }
 
     fn serialize_i16(self, v: i16) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u16>()];
+                self.output.try_extend(varint_u16(zig_zag_i16(v), &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_i32(self, v: i32) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u32>()];
+                self.output.try_extend(varint_u32(zig_zag_i32(v), &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u64>()];
+                self.output.try_extend(varint_u64(zig_zag_i64(v), &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_i128(self, v: i128) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u128>()];
+                self.output
+                    .try_extend(varint_u128(zig_zag_i128(v), &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
@@ -84,27 +138,47 @@ where
     }
 
     fn serialize_u16(self, v: u16) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u16>()];
+                self.output.try_extend(varint_u16(v, &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_u32(self, v: u32) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u32>()];
+                self.output.try_extend(varint_u32(v, &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u64>()];
+                self.output.try_extend(varint_u64(v, &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_u128(self, v: u128) -> Result<()> {
-        self.output
-            .try_extend(&v.to_le_bytes())
-            .map_err(|_| Error::SerializeBufferFull)
+        match self.int_encoding {
+            IntEncoding::Fixed => self.output.try_extend(&v.to_le_bytes()),
+            IntEncoding::Varint => {
+                let mut buf = [0u8; varint_max::<u128>()];
+                self.output.try_extend(varint_u128(v, &mut buf))
+            }
+        }
+        .map_err(|_| Error::SerializeBufferFull)
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
@@ -201,10 +275,7 @@ where
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.output
-            .try_push_varint_usize(&VarintUsize(len.ok_or(Error::SerializeSeqLengthUnknown)?))
-            .map_err(|_| Error::SerializeBufferFull)?;
-        Ok(self)
+        SeqSerializer::new(self, len)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
@@ -233,10 +304,7 @@ where
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.output
-            .try_push_varint_usize(&VarintUsize(len.ok_or(Error::SerializeSeqLengthUnknown)?))
-            .map_err(|_| Error::SerializeBufferFull)?;
-        Ok(self)
+        SeqSerializer::new(self, len)
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
@@ -256,15 +324,180 @@ where
         Ok(self)
     }
 
-    fn collect_str<T: ?Sized>(self, _value: &T) -> Result<Self::Ok>
+    fn collect_str<T>(self, value: &T) -> Result<Self::Ok>
     where
-        T: core::fmt::Display,
+        T: core::fmt::Display + ?Sized,
     {
-        unreachable!()
+        use core::fmt::Write;
+
+        // Format the value twice, first to count the length, so that the string does not
+        // need to be buffered anywhere
+        let mut counter = CountWriter { len: 0 };
+        write!(counter, "{}", value)
+            .map_err(|_| <Error as ser::Error>::custom("Display returned an error"))?;
+
+        self.output
+            .try_push_varint_usize(&VarintUsize(counter.len))
+            .map_err(|_| Error::SerializeBufferFull)?;
+
+        let mut writer = FlavorWriter {
+            output: &mut self.output,
+            remaining: counter.len,
+            full: false,
+            overlong: false,
+        };
+        let res = write!(writer, "{}", value);
+        if writer.full {
+            return Err(Error::SerializeBufferFull);
+        }
+        if writer.overlong || (res.is_ok() && writer.remaining != 0) {
+            return Err(<Error as ser::Error>::custom(
+                "Display output changed between passes",
+            ));
+        }
+        res.map_err(|_| <Error as ser::Error>::custom("Display returned an error"))
+    }
+}
+
+/// Counts the number of bytes written
+struct CountWriter {
+    len: usize,
+}
+
+impl core::fmt::Write for CountWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.len += s.len();
+        Ok(())
     }
 }
 
-impl<'a, F> ser::SerializeSeq for &'a mut Serializer<F>
+/// Writes to a flavor, failing if more than `remaining` bytes are written
+struct FlavorWriter<'a, F> {
+    output: &'a mut F,
+    remaining: usize,
+    // Whether the flavor ran out of space
+    full: bool,
+    // Whether more bytes were written than were counted on the first pass
+    overlong: bool,
+}
+
+impl<'a, F: SerFlavor> core::fmt::Write for FlavorWriter<'a, F> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.remaining = self.remaining.checked_sub(s.len()).ok_or_else(|| {
+            self.overlong = true;
+            core::fmt::Error
+        })?;
+        self.output.try_extend(s.as_bytes()).map_err(|_| {
+            self.full = true;
+            core::fmt::Error
+        })
+    }
+}
+
+/// Serializes the elements of a sequence, or the entries of a map.
+///
+/// The length is written up front when it is known. Otherwise, space for the largest
+/// varint length is reserved, if the flavor supports [`SerFlavor::try_patch()`], and
+/// the length is filled in once all elements are serialized. The reserved varint is
+/// padded with continuation bits to five bytes, or to three bytes on targets with a
+/// 16-bit `usize`. It can be read by deserializers on targets with a `usize` at least
+/// as wide, but deserializers on 16-bit targets reject the five byte lengths. With
+/// the `alloc` feature, the elements are buffered for all other flavors.
+pub struct SeqSerializer<'a, F>
+where
+    F: SerFlavor,
+{
+    ser: &'a mut Serializer<F>,
+    len: SeqLength,
+}
+
+enum SeqLength {
+    /// The length was known, and has already been written
+    Known,
+    /// The length is patched in at the given position, once all elements are serialized
+    Patch { pos: usize, count: usize },
+    /// The elements are buffered, and written after the length once all are serialized
+    #[cfg(feature = "alloc")]
+    Buffer {
+        ser: Serializer<AllocVec>,
+        count: usize,
+    },
+}
+
+impl<'a, F> SeqSerializer<'a, F>
+where
+    F: SerFlavor,
+{
+    fn new(ser: &'a mut Serializer<F>, len: Option<usize>) -> Result<Self> {
+        let len = match (len, ser.output.position()) {
+            (Some(len), _) => {
+                ser.output
+                    .try_push_varint_usize(&VarintUsize(len))
+                    .map_err(|_| Error::SerializeBufferFull)?;
+                SeqLength::Known
+            }
+            (None, Some(pos)) => {
+                ser.output
+                    .try_extend(&varint_u32_padded::<SEQ_LEN_PADDED>(0))
+                    .map_err(|_| Error::SerializeBufferFull)?;
+                SeqLength::Patch { pos, count: 0 }
+            }
+            #[cfg(feature = "alloc")]
+            (None, None) => SeqLength::Buffer {
+                ser: Serializer::with_int_encoding(AllocVec(Vec::new()), ser.int_encoding),
+                count: 0,
+            },
+            #[cfg(not(feature = "alloc"))]
+            (None, None) => return Err(Error::SerializeSeqLengthUnknown),
+        };
+        Ok(SeqSerializer { ser, len })
+    }
+
+    /// Serialize a value, counting it towards the length if `counted` is set
+    fn serialize<T>(&mut self, value: &T, counted: bool) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match &mut self.len {
+            SeqLength::Known => value.serialize(&mut *self.ser),
+            SeqLength::Patch { count, .. } => {
+                *count += usize::from(counted);
+                value.serialize(&mut *self.ser)
+            }
+            #[cfg(feature = "alloc")]
+            SeqLength::Buffer { ser, count } => {
+                *count += usize::from(counted);
+                value.serialize(ser)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.len {
+            SeqLength::Known => Ok(()),
+            SeqLength::Patch { pos, count } => {
+                let count = u32::try_from(count).map_err(|_| Error::SerializeBufferFull)?;
+                self.ser
+                    .output
+                    .try_patch(pos, &varint_u32_padded::<SEQ_LEN_PADDED>(count))
+                    .map_err(|_| Error::SerializeBufferFull)
+            }
+            #[cfg(feature = "alloc")]
+            SeqLength::Buffer { ser, count } => {
+                self.ser
+                    .output
+                    .try_push_varint_usize(&VarintUsize(count))
+                    .map_err(|_| Error::SerializeBufferFull)?;
+                self.ser
+                    .output
+                    .try_extend(&ser.output.0)
+                    .map_err(|_| Error::SerializeBufferFull)
+            }
+        }
+    }
+}
+
+impl<'a, F> ser::SerializeSeq for SeqSerializer<'a, F>
 where
     F: SerFlavor,
 {
@@ -278,16 +511,16 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut **self)
+        self.serialize(value, true)
     }
 
     // Close the sequence.
     fn end(self) -> Result<()> {
-        Ok(())
+        self.finish()
     }
 }
 
-impl<'a, F> ser::SerializeTuple for &'a mut Serializer<F>
+impl<F> ser::SerializeTuple for &mut Serializer<F>
 where
     F: SerFlavor,
 {
@@ -306,7 +539,7 @@ where
     }
 }
 
-impl<'a, F> ser::SerializeTupleStruct for &'a mut Serializer<F>
+impl<F> ser::SerializeTupleStruct for &mut Serializer<F>
 where
     F: SerFlavor,
 {
@@ -325,7 +558,7 @@ where
     }
 }
 
-impl<'a, F> ser::SerializeTupleVariant for &'a mut Serializer<F>
+impl<F> ser::SerializeTupleVariant for &mut Serializer<F>
 where
     F: SerFlavor,
 {
@@ -344,7 +577,7 @@ where
     }
 }
 
-impl<'a, F> ser::SerializeMap for &'a mut Serializer<F>
+impl<'a, F> ser::SerializeMap for SeqSerializer<'a, F>
 where
     F: SerFlavor,
 {
@@ -355,22 +588,22 @@ where
     where
         T: ?Sized + Serialize,
     {
-        key.serialize(&mut **self)
+        self.serialize(key, true)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(&mut **self)
+        self.serialize(value, false)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.finish()
     }
 }
 
-impl<'a, F> ser::SerializeStruct for &'a mut Serializer<F>
+impl<F> ser::SerializeStruct for &mut Serializer<F>
 where
     F: SerFlavor,
 {
@@ -389,7 +622,7 @@ where
     }
 }
 
-impl<'a, F> ser::SerializeStructVariant for &'a mut Serializer<F>
+impl<F> ser::SerializeStructVariant for &mut Serializer<F>
 where
     F: SerFlavor,
 {
@@ -0,0 +1,49 @@
+//! Constants and helpers for SLIP (RFC 1055) framing.
+
+use crate::error::{Error, Result};
+
+/// Marks the end of a frame
+pub(crate) const END: u8 = 0xC0;
+/// Starts an escape sequence
+pub(crate) const ESC: u8 = 0xDB;
+/// Escaped `END` byte, following `ESC`
+pub(crate) const ESC_END: u8 = 0xDC;
+/// Escaped `ESC` byte, following `ESC`
+pub(crate) const ESC_ESC: u8 = 0xDD;
+
+/// Decode the first SLIP frame in `buf` in place, skipping any leading `END` bytes.
+/// If there is no terminating `END` byte, the frame ends with the buffer.
+///
+/// Returns the range of `buf` holding the decoded frame, and the number of
+/// bytes of `buf` that were consumed.
+pub(crate) fn decode_in_place(buf: &mut [u8]) -> Result<(core::ops::Range<usize>, usize)> {
+    let start = buf.iter().position(|b| *b != END).unwrap_or(buf.len());
+    let mut dest = start;
+    let mut src = start;
+
+    while src < buf.len() {
+        let byte = buf[src];
+        src += 1;
+        buf[dest] = match byte {
+            END => return Ok((start..dest, src)),
+            ESC => {
+                let escaped = buf.get(src).ok_or(Error::DeserializeBadEncoding)?;
+                src += 1;
+                unescape(*escaped)?
+            }
+            _ => byte,
+        };
+        dest += 1;
+    }
+
+    Ok((start..dest, src))
+}
+
+/// Get the original value of an escaped byte
+pub(crate) fn unescape(byte: u8) -> Result<u8> {
+    match byte {
+        ESC_END => Ok(END),
+        ESC_ESC => Ok(ESC),
+        _ => Err(Error::DeserializeBadEncoding),
+    }
+}
@@ -1,5 +1,4 @@
 use core::{
-    convert::Infallible,
     ops::{Deref, DerefMut},
     str,
 };
@@ -41,7 +40,7 @@ pub trait StringRW {
 
 #[cfg(feature = "use-std")]
 impl StringRW for std::string::String {
-    type Error = Infallible;
+    type Error = core::convert::Infallible;
 
     fn capacity(&self) -> usize {
         self.capacity()
@@ -13,7 +13,7 @@ pub struct VarintUsize(pub usize);
 pub type VarintBuf = [u8; VarintUsize::varint_usize_max()];
 
 impl VarintUsize {
-    pub fn to_buf<'a, 'b>(&'a self, out: &'b mut VarintBuf) -> &'b mut [u8] {
+    pub fn to_buf<'b>(&self, out: &'b mut VarintBuf) -> &'b mut [u8] {
         let mut value = self.0;
         for i in 0..Self::varint_usize_max() {
             out[i] = (value & 0x7F) as u8;
@@ -48,3 +48,126 @@ impl VarintUsize {
         roundup_bits / BITS_PER_VARINT_BYTE
     }
 }
+
+/// Selects how integers wider than a single byte are placed on the wire.
+///
+/// This applies to `u16`, `u32`, `u64`, `u128` and their signed counterparts.
+/// `u8`/`i8` are always a single byte, and lengths and enum discriminants
+/// are always encoded as varints, regardless of this setting.
+///
+/// The serializer and deserializer MUST agree on the encoding, as it is not
+/// recorded in the message itself.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "use-defmt", derive(defmt::Format))]
+pub enum IntEncoding {
+    /// Integers are encoded as fixed width, little endian bytes. This is the default.
+    Fixed,
+
+    /// Unsigned integers are encoded as [Varint]s. Signed integers are first
+    /// [zigzag] encoded, so that values close to zero stay small on the wire.
+    ///
+    /// [Varint]: https://developers.google.com/protocol-buffers/docs/encoding
+    /// [zigzag]: https://developers.google.com/protocol-buffers/docs/encoding#signed-ints
+    Varint,
+}
+
+// Not derived, as `#[default]` on enum variants needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for IntEncoding {
+    fn default() -> Self {
+        IntEncoding::Fixed
+    }
+}
+
+/// The largest number of bytes needed to store a `T` as a varint
+pub(crate) const fn varint_max<T>() -> usize {
+    const BITS_PER_BYTE: usize = 8;
+    const BITS_PER_VARINT_BYTE: usize = 7;
+
+    let bits = core::mem::size_of::<T>() * BITS_PER_BYTE;
+    let roundup_bits = bits + (BITS_PER_VARINT_BYTE - 1);
+    roundup_bits / BITS_PER_VARINT_BYTE
+}
+
+/// The largest value allowed in the final byte of a maximum length varint of a `T`.
+/// Anything larger would not fit in a `T`.
+pub(crate) const fn max_of_last_byte<T>() -> u8 {
+    let bits = core::mem::size_of::<T>() * 8;
+    let extra_bits = bits % 7;
+    (1 << extra_bits) - 1
+}
+
+macro_rules! impl_varint_encode {
+    ($($name:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub(crate) fn $name(value: $ty, out: &mut [u8; varint_max::<$ty>()]) -> &[u8] {
+                let mut value = value;
+                for i in 0..varint_max::<$ty>() {
+                    out[i] = (value & 0x7F) as u8;
+                    value >>= 7;
+                    if value != 0 {
+                        out[i] |= 0x80;
+                    } else {
+                        return &out[..=i];
+                    }
+                }
+                debug_assert_eq!(value, 0);
+                &out[..]
+            }
+        )*
+    };
+}
+
+impl_varint_encode! {
+    varint_u16: u16,
+    varint_u32: u32,
+    varint_u64: u64,
+    varint_u128: u128,
+}
+
+/// Encode a `u32` as a varint which always uses `N` bytes, by setting the continuation bit on
+/// all but the last byte. This allows the value to be written later, into space that was
+/// reserved up front. The value must fit into `N` varint bytes.
+pub(crate) fn varint_u32_padded<const N: usize>(value: u32) -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut value = value;
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if i != N - 1 {
+            *byte |= 0x80;
+        }
+    }
+    debug_assert_eq!(value, 0);
+    out
+}
+
+/// The number of bytes of a padded varint length of a sequence. This is the largest number of
+/// bytes the deserializer accepts for a `usize` on this target, but no more than is needed for
+/// a `u32`, as longer sequences are rejected when serializing anyway.
+pub(crate) const SEQ_LEN_PADDED: usize = if varint_max::<usize>() < varint_max::<u32>() {
+    varint_max::<usize>()
+} else {
+    varint_max::<u32>()
+};
+
+macro_rules! impl_zig_zag {
+    ($($enc:ident, $dec:ident: $sty:ty => $uty:ty),* $(,)?) => {
+        $(
+            pub(crate) fn $enc(n: $sty) -> $uty {
+                ((n << 1) ^ (n >> (<$sty>::BITS - 1))) as $uty
+            }
+
+            pub(crate) fn $dec(n: $uty) -> $sty {
+                ((n >> 1) as $sty) ^ (-((n & 0b1) as $sty))
+            }
+        )*
+    };
+}
+
+impl_zig_zag! {
+    zig_zag_i16, de_zig_zag_i16: i16 => u16,
+    zig_zag_i32, de_zig_zag_i32: i32 => u32,
+    zig_zag_i64, de_zig_zag_i64: i64 => u64,
+    zig_zag_i128, de_zig_zag_i128: i128 => u128,
+}
@@ -1,4 +1,8 @@
-use postcard::{CobsAccumulator, FeedResult};
+use postcard::{
+    decoders::{Decoded, FrameDecoder},
+    Accumulator, AccumulatorStats, CobsAccumulator, Error, FeedResult, FrameError, FrameMonitor,
+    StatsMonitor,
+};
 use serde::{Deserialize, Serialize};
 use std::io::Read;
 
@@ -55,3 +59,187 @@ fn reader() {
 
     assert_eq!(output.unwrap(), expected);
 }
+
+#[test]
+fn cobs_frames() {
+    // Long runs without zeros are split into several COBS blocks
+    let long: Vec<u8> = (1..=255).cycle().take(600).collect();
+    let mut buf = [0u8; 640];
+    let frame = postcard::to_slice_cobs(&long, &mut buf).unwrap();
+
+    let mut stream = vec![0x00, 0x00];
+    stream.extend_from_slice(frame);
+    // A frame ending in the middle of a block
+    stream.extend_from_slice(&[0x03, 0x01, 0x00]);
+    stream.extend_from_slice(frame);
+
+    for chunk_size in 1..=stream.len() {
+        let mut acc: CobsAccumulator<608> = CobsAccumulator::new();
+        let mut results = Vec::new();
+
+        for chunk in stream.chunks(chunk_size) {
+            let mut window = chunk;
+            while !window.is_empty() {
+                window = match acc.feed::<Vec<u8>>(window) {
+                    FeedResult::Consumed => break,
+                    FeedResult::OverFull(_) => panic!("overfull"),
+                    FeedResult::DeserError(rest) => {
+                        results.push(None);
+                        rest
+                    }
+                    FeedResult::Success { data, remaining } => {
+                        results.push(Some(data));
+                        remaining
+                    }
+                };
+            }
+        }
+
+        assert_eq!(results, &[Some(long.clone()), None, Some(long.clone())]);
+    }
+}
+
+#[test]
+fn cobs_overfull() {
+    let mut big_buf = [0u8; 64];
+    let big = postcard::to_slice_cobs(&[0x00u8; 40][..], &mut big_buf).unwrap();
+    let mut small_buf = [0u8; 8];
+    let small = postcard::to_slice_cobs(&(1u8, 2u8), &mut small_buf).unwrap();
+
+    let mut stream = big.to_vec();
+    stream.extend_from_slice(small);
+
+    let mut acc: CobsAccumulator<16> = CobsAccumulator::new();
+    let remaining = match acc.feed::<(u8, u8)>(&stream) {
+        FeedResult::OverFull(remaining) => remaining,
+        _ => panic!(),
+    };
+    assert_eq!(remaining, small);
+    match acc.feed::<(u8, u8)>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, (1, 2));
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
+
+// Frames terminated by a newline, with the message in hex
+#[derive(Default)]
+struct HexLines {
+    high: Option<u8>,
+    invalid: bool,
+}
+
+impl FrameDecoder for HexLines {
+    fn decode(&mut self, byte: u8) -> Decoded {
+        if byte == b'\n' {
+            let state = std::mem::take(self);
+            return if state.invalid || state.high.is_some() {
+                Decoded::Invalid
+            } else {
+                Decoded::End
+            };
+        }
+
+        let nibble = match (byte as char).to_digit(16) {
+            Some(nibble) => nibble as u8,
+            None => {
+                self.invalid = true;
+                return Decoded::Nothing;
+            }
+        };
+        match self.high.take() {
+            Some(high) => Decoded::Byte(high << 4 | nibble),
+            None => {
+                self.high = Some(nibble);
+                Decoded::Nothing
+            }
+        }
+    }
+}
+
+#[test]
+fn custom_decoder() {
+    let mut acc: Accumulator<HexLines, 8> = Accumulator::default();
+    let stream = b"0102\n01x2\n030405\n";
+
+    let remaining = match acc.feed::<(u8, u8)>(stream) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, (1, 2));
+            remaining
+        }
+        _ => panic!(),
+    };
+    let remaining = match acc.feed::<(u8, u8)>(remaining) {
+        FeedResult::DeserError(remaining) => remaining,
+        _ => panic!(),
+    };
+    match acc.feed::<[u8; 3]>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, [3, 4, 5]);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
+
+/// Records the discarded frames
+#[derive(Default)]
+struct Failed {
+    frames: Vec<(FrameError, Vec<u8>)>,
+}
+
+impl FrameMonitor for Failed {
+    fn frame_discarded(&mut self, error: &FrameError, frame: &[u8]) {
+        self.frames.push((error.clone(), frame.to_vec()));
+    }
+}
+
+#[test]
+fn stats_and_monitor() {
+    let mut acc: CobsAccumulator<4, (StatsMonitor, Failed)> = CobsAccumulator::default();
+
+    let mut stream = Vec::new();
+    // Ok
+    stream.extend_from_slice(&[0x02, 0x05, 0x00]);
+    // Too long
+    stream.extend_from_slice(&[0x07, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x00]);
+    // Ends in the middle of a block
+    stream.extend_from_slice(&[0x03, 0x09, 0x00]);
+    // Decodes fine, but is not a `u8`
+    stream.extend_from_slice(&[0x01, 0x00]);
+
+    let mut window = &stream[..];
+    while !window.is_empty() {
+        window = match acc.feed::<u8>(window) {
+            FeedResult::Consumed => break,
+            FeedResult::OverFull(rest) | FeedResult::DeserError(rest) => rest,
+            FeedResult::Success { remaining, .. } => remaining,
+        };
+    }
+
+    assert_eq!(
+        acc.monitor().0.stats(),
+        AccumulatorStats {
+            frames_ok: 1,
+            overflows: 1,
+            decode_errors: 2,
+            bytes_discarded: 13,
+        }
+    );
+    assert_eq!(
+        acc.monitor().1.frames,
+        &[
+            (FrameError::OverFull, vec![0x01, 0x02, 0x03, 0x04]),
+            (FrameError::Invalid, vec![0x09]),
+            (
+                FrameError::Deserialize(Error::DeserializeUnexpectedEnd),
+                vec![]
+            ),
+        ]
+    );
+
+    acc.monitor_mut().0.reset();
+    assert_eq!(acc.monitor().0.stats(), AccumulatorStats::default());
+}
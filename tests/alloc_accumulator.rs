@@ -0,0 +1,87 @@
+#![cfg(feature = "alloc")]
+
+use postcard::{to_allocvec_cobs, AllocCobsAccumulator, FeedResult};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Message {
+    id: u16,
+    body: Vec<u8>,
+}
+
+#[test]
+fn large_frames() {
+    let msg = Message {
+        id: 7,
+        body: (0..=255).cycle().take(5000).collect(),
+    };
+    let frame = to_allocvec_cobs(&msg).unwrap();
+
+    let mut acc = AllocCobsAccumulator::new(8192);
+    let mut chunks = frame.chunks(64).peekable();
+    while let Some(chunk) = chunks.next() {
+        match acc.feed::<Message>(chunk) {
+            FeedResult::Consumed => assert!(chunks.peek().is_some()),
+            FeedResult::Success { data, remaining } => {
+                assert!(chunks.peek().is_none());
+                assert!(remaining.is_empty());
+                assert_eq!(data, msg);
+            }
+            _ => panic!(),
+        }
+    }
+}
+
+#[test]
+fn bounded() {
+    let big = to_allocvec_cobs(&Message {
+        id: 1,
+        body: vec![0xAA; 100],
+    })
+    .unwrap();
+    let small_msg = Message {
+        id: 2,
+        body: vec![0x00; 4],
+    };
+    let small = to_allocvec_cobs(&small_msg).unwrap();
+
+    let mut acc = AllocCobsAccumulator::new(32);
+    let (first, second) = big.split_at(50);
+    assert!(matches!(acc.feed::<Message>(first), FeedResult::Consumed));
+
+    let mut stream = second.to_vec();
+    stream.extend_from_slice(&small);
+    let remaining = match acc.feed::<Message>(&stream) {
+        FeedResult::OverFull(remaining) => remaining,
+        _ => panic!(),
+    };
+    assert_eq!(remaining, &small[..]);
+
+    match acc.feed::<Message>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, small_msg);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn borrowed() {
+    let frame = to_allocvec_cobs(&("hello", &[1u8, 2, 3][..])).unwrap();
+    let mut stream = frame.clone();
+    stream.extend_from_slice(&[0x01, 0x00]);
+
+    let mut acc = AllocCobsAccumulator::new(64);
+    let remaining = match acc.feed_ref::<(&str, &[u8])>(&stream) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, ("hello", &[1u8, 2, 3][..]));
+            remaining
+        }
+        _ => panic!(),
+    };
+    assert!(matches!(
+        acc.feed_ref::<(&str, &[u8])>(remaining),
+        FeedResult::DeserError(&[])
+    ));
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "bytes")]
+
+use bytes::{BufMut, Bytes, BytesMut};
+use postcard::{
+    de_flavors::BufFlavor, from_buf_chunks, from_bytes, to_bufmut, to_slice, Deserializer, Error,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Packet<'a> {
+    seq: u64,
+    offset: i128,
+    name: &'a str,
+    payload: Option<&'a [u8]>,
+    flags: [bool; 3],
+}
+
+const PACKET: Packet<'static> = Packet {
+    seq: 0x0123_4567_89AB_CDEF,
+    offset: -1,
+    name: "telemetry",
+    payload: Some(&[0x00, 0xFF, 0x10, 0x20]),
+    flags: [true, false, true],
+};
+
+#[test]
+fn bufmut() {
+    let mut expected = [0u8; 64];
+    let expected = to_slice(&PACKET, &mut expected).unwrap();
+
+    let mut buf = BytesMut::new();
+    buf.put_u16(0xAAAA);
+    let buf = to_bufmut(&PACKET, buf).unwrap();
+    assert_eq!(&buf[..2], &[0xAA, 0xAA]);
+    assert_eq!(&buf[2..], &expected[..]);
+    assert_eq!(from_bytes::<Packet>(&buf[2..]).unwrap(), PACKET);
+
+    let mut out = [0u8; 64];
+    let rest = to_bufmut(&PACKET, &mut out[..]).unwrap();
+    assert_eq!(rest.len(), 64 - expected.len());
+    assert_eq!(&out[..expected.len()], &expected[..]);
+
+    assert_eq!(
+        to_bufmut(&PACKET, &mut out[..expected.len() - 1]).err(),
+        Some(Error::SerializeBufferFull)
+    );
+}
+
+#[test]
+fn buf_chunks() {
+    let mut data = [0u8; 64];
+    let used = to_slice(&PACKET, &mut data).unwrap().len();
+    data[used] = 0x42;
+    let data = &data[..used + 1];
+
+    for size in 1..=data.len() {
+        let chunks: Vec<&[u8]> = data.chunks(size).collect();
+        let mut scratch = [0u8; 32];
+        let (out, read) = from_buf_chunks::<Packet, _>(&chunks, &mut scratch).unwrap();
+        assert_eq!(out, PACKET, "chunk size {}", size);
+        assert_eq!(read, used);
+    }
+
+    // Only the borrowed fields spanning chunks use up the scratch buffer. With chunks of three
+    // bytes, both the name and the payload span chunks.
+    let chunks: Vec<&[u8]> = data.chunks(3).collect();
+    let mut scratch = [0u8; 16];
+    let mut deserializer = Deserializer::from_flavor(BufFlavor::new(&chunks, &mut scratch));
+    let out = Packet::deserialize(&mut deserializer).unwrap();
+    assert_eq!(out, PACKET);
+    let (_, unused) = deserializer.finalize().unwrap();
+    assert_eq!(unused.len(), 16 - PACKET.name.len() - 4);
+}
+
+#[test]
+fn buf_borrowed() {
+    let mut data = [0u8; 64];
+    let data = to_slice(&PACKET, &mut data).unwrap();
+    // Split in the middle of the 16 byte offset, so the name and payload are contained in the
+    // second chunk
+    let (head, tail) = data.split_at(14);
+    let chunks = [Bytes::copy_from_slice(head), Bytes::copy_from_slice(tail)];
+
+    let mut scratch = [0u8; 16];
+    let mut deserializer = Deserializer::from_flavor(BufFlavor::new(&chunks, &mut scratch));
+    let out = Packet::deserialize(&mut deserializer).unwrap();
+    assert_eq!(out, PACKET);
+
+    // Borrowed from the chunk, without copying
+    let tail = chunks[1].as_ptr_range();
+    assert!(tail.contains(&out.name.as_ptr()));
+    assert!(tail.contains(&out.payload.unwrap().as_ptr()));
+
+    let (read, unused) = deserializer.finalize().unwrap();
+    assert_eq!(read, data.len());
+    assert_eq!(unused.len(), 16);
+}
+
+#[cfg(feature = "use-std")]
+#[test]
+fn buf_advance() {
+    use bytes::Buf;
+
+    #[derive(Deserialize, Debug, PartialEq, Eq)]
+    struct Owned {
+        seq: u64,
+        offset: i128,
+        name: String,
+        payload: Option<Vec<u8>>,
+        flags: [bool; 3],
+    }
+
+    let mut data = [0u8; 64];
+    let used = to_slice(&PACKET, &mut data).unwrap().len();
+    data[used] = 0x42;
+    let (head, tail) = data[..used + 1].split_at(14);
+    let mut chain = Bytes::copy_from_slice(head).chain(Bytes::copy_from_slice(tail));
+
+    let out: Owned = postcard::from_buf(&mut chain, &mut [0u8; 16]).unwrap();
+    assert_eq!(out.name, PACKET.name);
+    assert_eq!(out.payload.as_deref(), PACKET.payload);
+    assert_eq!(chain.remaining(), 1);
+    assert_eq!(chain.chunk(), &[0x42]);
+
+    // A message running past the end of the `Buf` leaves it untouched
+    let mut short = Bytes::copy_from_slice(&data[..used - 1]);
+    assert_eq!(
+        postcard::from_buf::<Owned, _>(&mut short, &mut [0u8; 16]).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+    assert_eq!(short.len(), used - 1);
+}
+
+#[test]
+fn buf_errors() {
+    let mut data = [0u8; 64];
+    let data = to_slice(&PACKET, &mut data).unwrap();
+
+    assert_eq!(
+        from_buf_chunks::<Packet, _>(&[&data[..data.len() - 1]], &mut [0u8; 16]).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+    let chunks: Vec<&[u8]> = data.chunks(3).collect();
+    assert_eq!(
+        from_buf_chunks::<Packet, _>(&chunks, &mut [0u8; 8]).err(),
+        Some(Error::DeserializeScratchTooSmall)
+    );
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "tokio-util")]
+
+use futures::{executor::block_on, SinkExt, StreamExt};
+use postcard::{to_stdvec_cobs, CodecError, Error, PostcardCobsCodec};
+use serde::{Deserialize, Serialize};
+use tokio_util::{
+    bytes::BytesMut,
+    codec::{Decoder, Encoder, FramedRead, FramedWrite},
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+enum Event {
+    Connected { id: u32, name: String },
+    Data(Vec<u8>),
+    Disconnected(u32),
+}
+
+fn events() -> Vec<Event> {
+    vec![
+        Event::Connected {
+            id: 7,
+            name: "sensor".to_string(),
+        },
+        Event::Data(vec![0x00; 40]),
+        Event::Data(vec![]),
+        Event::Disconnected(7),
+    ]
+}
+
+#[test]
+fn framed() {
+    let mut sink = FramedWrite::new(Vec::new(), PostcardCobsCodec::<Event>::new(64));
+    block_on(async {
+        for event in events() {
+            sink.send(event).await.unwrap();
+        }
+    });
+    let wire = sink.into_inner();
+    assert_eq!(wire.iter().filter(|b| **b == 0).count(), 4);
+
+    let stream = FramedRead::new(&wire[..], PostcardCobsCodec::<Event>::new(64));
+    let out: Vec<Event> = block_on(stream.map(Result::unwrap).collect());
+    assert_eq!(out, events());
+}
+
+#[test]
+fn chunked() {
+    let mut wire = vec![0x00];
+    for event in events() {
+        wire.extend(to_stdvec_cobs(&event).unwrap());
+        wire.push(0x00);
+    }
+
+    let mut codec = PostcardCobsCodec::<Event>::new(64);
+    let mut buf = BytesMut::new();
+    let mut out = Vec::new();
+    for chunk in wire.chunks(3) {
+        buf.extend_from_slice(chunk);
+        while let Some(event) = codec.decode(&mut buf).unwrap() {
+            out.push(event);
+        }
+    }
+    assert_eq!(out, events());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn too_long() {
+    let mut codec = PostcardCobsCodec::<Event>::new(8);
+    let mut buf = BytesMut::new();
+    assert!(matches!(
+        codec.encode(Event::Data(vec![1; 8]), &mut buf),
+        Err(CodecError::Postcard(Error::SerializeBufferFull))
+    ));
+    assert!(buf.is_empty());
+    codec.encode(Event::Data(vec![1; 5]), &mut buf).unwrap();
+    assert_eq!(buf.len(), 9);
+
+    // A frame that fails to encode does not disturb the frames before it
+    assert!(matches!(
+        codec.encode(Event::Data(vec![1; 8]), &mut buf),
+        Err(CodecError::Postcard(Error::SerializeBufferFull))
+    ));
+    assert_eq!(buf.len(), 9);
+
+    // A complete frame that is too long
+    let long = to_stdvec_cobs(&Event::Data(vec![1; 8])).unwrap();
+    let mut buf = BytesMut::from(&long[..]);
+    buf.extend(to_stdvec_cobs(&Event::Disconnected(1)).unwrap());
+    assert!(matches!(
+        codec.decode(&mut buf),
+        Err(CodecError::Postcard(Error::DeserializeBufferFull))
+    ));
+    assert_eq!(
+        codec.decode(&mut buf).unwrap(),
+        Some(Event::Disconnected(1))
+    );
+
+    // A partial frame that is already too long, which is discarded as it arrives
+    let mut buf = BytesMut::from(&long[..9]);
+    assert!(matches!(
+        codec.decode(&mut buf),
+        Err(CodecError::Postcard(Error::DeserializeBufferFull))
+    ));
+    assert!(buf.is_empty());
+    buf.extend_from_slice(&long[9..]);
+    buf.extend(to_stdvec_cobs(&Event::Disconnected(2)).unwrap());
+    assert_eq!(
+        codec.decode(&mut buf).unwrap(),
+        Some(Event::Disconnected(2))
+    );
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn bad_frame() {
+    let mut codec = PostcardCobsCodec::<Event>::new(64);
+    let mut buf = BytesMut::from(&[0x05, 0x01, 0x00][..]);
+    buf.extend(to_stdvec_cobs(&Event::Disconnected(3)).unwrap());
+    assert!(matches!(
+        codec.decode(&mut buf),
+        Err(CodecError::Postcard(Error::DeserializeBadEncoding))
+    ));
+    assert_eq!(
+        codec.decode(&mut buf).unwrap(),
+        Some(Event::Disconnected(3))
+    );
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "use-crc")]
+
+use crc::{Crc, CRC_16_IBM_SDLC, CRC_32_ISCSI, CRC_64_ECMA_182, CRC_8_SMBUS};
+use postcard::{
+    decoders,
+    flavors::{self, Cobs, Slice},
+    from_bytes_crc, serialize_with_flavor, take_from_bytes_crc, to_slice_crc, Accumulator, Error,
+    FeedResult,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Telemetry<'a> {
+    seq: u32,
+    name: &'a str,
+    flags: [u8; 4],
+}
+
+const CRC8: Crc<u8> = Crc::<u8>::new(&CRC_8_SMBUS);
+const CRC16: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_SDLC);
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+const CRC64: Crc<u64> = Crc::<u64>::new(&CRC_64_ECMA_182);
+
+fn data() -> Telemetry<'static> {
+    Telemetry {
+        seq: 0x0102_0304,
+        name: "sensor",
+        flags: [0x00, 0xFF, 0x00, 0x10],
+    }
+}
+
+#[test]
+fn crc_widths() {
+    let mut buf = [0u8; 64];
+
+    let plain_len = postcard::to_slice(&data(), &mut buf).unwrap().len();
+
+    let used = to_slice_crc(&data(), &mut buf, CRC8.digest()).unwrap();
+    assert_eq!(used.len(), plain_len + 1);
+    assert_eq!(
+        used[plain_len..],
+        CRC8.checksum(&used[..plain_len]).to_le_bytes()
+    );
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC8.digest()).unwrap(),
+        data()
+    );
+
+    let used = to_slice_crc(&data(), &mut buf, CRC16.digest()).unwrap();
+    assert_eq!(used.len(), plain_len + 2);
+    assert_eq!(
+        used[plain_len..],
+        CRC16.checksum(&used[..plain_len]).to_le_bytes()
+    );
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC16.digest()).unwrap(),
+        data()
+    );
+
+    let used = to_slice_crc(&data(), &mut buf, CRC32.digest()).unwrap();
+    assert_eq!(used.len(), plain_len + 4);
+    assert_eq!(
+        used[plain_len..],
+        CRC32.checksum(&used[..plain_len]).to_le_bytes()
+    );
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC32.digest()).unwrap(),
+        data()
+    );
+
+    let used = to_slice_crc(&data(), &mut buf, CRC64.digest()).unwrap();
+    assert_eq!(used.len(), plain_len + 8);
+    assert_eq!(
+        used[plain_len..],
+        CRC64.checksum(&used[..plain_len]).to_le_bytes()
+    );
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC64.digest()).unwrap(),
+        data()
+    );
+}
+
+#[test]
+fn crc_mismatch() {
+    let mut buf = [0u8; 64];
+    let used = to_slice_crc(&data(), &mut buf, CRC32.digest()).unwrap();
+
+    // Corrupt the payload
+    used[2] ^= 0x01;
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC32.digest()),
+        Err(Error::DeserializeBadCrc)
+    );
+    used[2] ^= 0x01;
+
+    // Corrupt the checksum
+    let last = used.len() - 1;
+    used[last] ^= 0x80;
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC32.digest()),
+        Err(Error::DeserializeBadCrc)
+    );
+
+    // Missing checksum
+    let used = postcard::to_slice(&data(), &mut buf).unwrap();
+    assert_eq!(
+        from_bytes_crc::<Telemetry, _>(used, CRC32.digest()),
+        Err(Error::DeserializeUnexpectedEnd)
+    );
+}
+
+#[test]
+fn crc_take_remainder() {
+    let mut buf = [0u8; 64];
+    let len = to_slice_crc(&data(), &mut buf, CRC16.digest())
+        .unwrap()
+        .len();
+    buf[len..len + 3].copy_from_slice(&[0xAA, 0xBB, 0xCC]);
+
+    let (out, rest) = take_from_bytes_crc::<Telemetry, _>(&buf[..len + 3], CRC16.digest()).unwrap();
+    assert_eq!(out, data());
+    assert_eq!(rest, &[0xAA, 0xBB, 0xCC]);
+}
+
+#[test]
+fn crc_in_cobs() {
+    let mut buf = [0u8; 64];
+    let frame = serialize_with_flavor::<Telemetry, flavors::Crc<Cobs<Slice>, _>, &mut [u8]>(
+        &data(),
+        flavors::Crc::new(Cobs::try_new(Slice::new(&mut buf)).unwrap(), CRC32.digest()),
+    )
+    .unwrap();
+
+    // Exactly one sentinel, at the end of the frame
+    assert_eq!(frame.iter().filter(|b| **b == 0).count(), 1);
+    assert_eq!(frame.last(), Some(&0));
+
+    let sz = cobs::decode_in_place(frame).unwrap();
+    let out: Telemetry = from_bytes_crc(&frame[..sz], CRC32.digest()).unwrap();
+    assert_eq!(out, data());
+}
+
+#[test]
+fn crc_accumulator() {
+    let mut buf = [0u8; 64];
+    let frame = serialize_with_flavor::<Telemetry, flavors::Crc<Cobs<Slice>, _>, &mut [u8]>(
+        &data(),
+        flavors::Crc::new(Cobs::try_new(Slice::new(&mut buf)).unwrap(), CRC16.digest()),
+    )
+    .unwrap();
+
+    let mut corrupt = frame.to_vec();
+    corrupt[3] ^= 0x01;
+    let mut stream = corrupt;
+    stream.extend_from_slice(frame);
+    // Too short to hold a checksum
+    stream.extend_from_slice(&[0x02, 0x01, 0x00]);
+
+    let mut acc: Accumulator<_, 32> =
+        Accumulator::with_decoder(decoders::Crc::new(decoders::Cobs::new(), CRC16.digest()));
+    let remaining = match acc.feed_ref::<Telemetry>(&stream) {
+        FeedResult::DeserError(remaining) => remaining,
+        _ => panic!(),
+    };
+    let remaining = match acc.feed_ref::<Telemetry>(remaining) {
+        FeedResult::Success {
+            data: out,
+            remaining,
+        } => {
+            assert_eq!(out, data());
+            remaining
+        }
+        _ => panic!(),
+    };
+    assert!(matches!(
+        acc.feed_ref::<Telemetry>(remaining),
+        FeedResult::DeserError(&[])
+    ));
+}
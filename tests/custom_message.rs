@@ -0,0 +1,78 @@
+#![cfg(any(feature = "alloc", feature = "heapless-custom-message"))]
+
+use postcard::{from_bytes, to_slice, Error};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An even number, which is validated when deserializing
+#[derive(Debug)]
+struct Even(u8);
+
+impl<'de> Deserialize<'de> for Even {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let val = u8::deserialize(deserializer)?;
+        if val % 2 != 0 {
+            return Err(de::Error::custom(format_args!("{} is odd", val)));
+        }
+        Ok(Even(val))
+    }
+}
+
+struct Refuse(&'static str);
+
+impl Serialize for Refuse {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom(self.0))
+    }
+}
+
+#[test]
+fn de_message() {
+    assert_eq!(from_bytes::<Even>(&[0x04]).unwrap().0, 4);
+
+    let err = from_bytes::<Even>(&[0x03]).unwrap_err();
+    match &err {
+        Error::SerdeDeCustom(msg) => assert_eq!(msg.as_str(), "3 is odd"),
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(err.to_string(), "Serde Deserialization Error: 3 is odd");
+}
+
+#[test]
+fn ser_message() {
+    let mut buf = [0u8; 8];
+    let err = to_slice(&Refuse("not today"), &mut buf).unwrap_err();
+    assert_eq!(err.to_string(), "Serde Serialization Error: not today");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn long_message() {
+    let mut buf = [0u8; 8];
+    let msg = "this message is longer than thirty-two bytes";
+    match to_slice(&Refuse(msg), &mut buf).unwrap_err() {
+        Error::SerdeSerCustom(custom) => assert_eq!(custom.as_str(), msg),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+#[test]
+fn long_message() {
+    use postcard::CUSTOM_MESSAGE_CAPACITY;
+
+    let mut buf = [0u8; 8];
+    let msg = "this message is longer than thirty-two bytes";
+    match to_slice(&Refuse(msg), &mut buf).unwrap_err() {
+        Error::SerdeSerCustom(custom) => {
+            assert_eq!(custom.as_str(), &msg[..CUSTOM_MESSAGE_CAPACITY])
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // Truncation does not split characters
+    let msg = "0123456789012345678901234567890ü";
+    match to_slice(&Refuse(msg), &mut buf).unwrap_err() {
+        Error::SerdeSerCustom(custom) => assert_eq!(custom.as_str(), &msg[..31]),
+        other => panic!("{:?}", other),
+    }
+}
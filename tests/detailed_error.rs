@@ -0,0 +1,130 @@
+use postcard::{from_bytes_detailed, take_from_bytes_detailed, Error};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+struct Header {
+    version: u8,
+    kind: Kind,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+enum Kind {
+    Ping,
+    Data { len: u16, valid: bool },
+    Many([Option<u8>; 3]),
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+struct Packet {
+    id: u32,
+    header: Header,
+    crc: u16,
+}
+
+#[test]
+fn success() {
+    let data = &[
+        0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x34, 0x12, 0x01, 0xCD, 0xAB, 0xFF,
+    ];
+    let (out, rest) = take_from_bytes_detailed::<Packet>(data).unwrap();
+    assert_eq!(
+        out,
+        Packet {
+            id: 1,
+            header: Header {
+                version: 2,
+                kind: Kind::Data {
+                    len: 0x1234,
+                    valid: true
+                },
+            },
+            crc: 0xABCD,
+        }
+    );
+    assert_eq!(rest, &[0xFF]);
+}
+
+#[test]
+fn offsets() {
+    // Unknown variants are reported by serde
+    let err = from_bytes_detailed::<Packet>(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x05]).unwrap_err();
+    assert!(matches!(err.kind(), Error::SerdeDeCustom(_)));
+    assert_eq!(err.offset(), 6);
+
+    let err = from_bytes_detailed::<Packet>(&[
+        0x01, 0x00, 0x00, 0x00, 0x02, 0x80, 0x80, 0x80, 0x80, 0x10,
+    ])
+    .unwrap_err();
+    assert_eq!(err.kind(), &Error::DeserializeBadEnum);
+    assert_eq!(err.offset(), 10);
+
+    let err =
+        from_bytes_detailed::<Packet>(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x34, 0x12, 0x07])
+            .unwrap_err();
+    assert_eq!(err.kind(), &Error::DeserializeBadBool);
+    assert_eq!(err.offset(), 9);
+
+    // Multi-byte values report the offset of their start
+    let err =
+        from_bytes_detailed::<Packet>(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0xCD]).unwrap_err();
+    assert_eq!(err.kind(), &Error::DeserializeUnexpectedEnd);
+    assert_eq!(err.offset(), 6);
+    assert_eq!(Error::from(err), Error::DeserializeUnexpectedEnd);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn paths() {
+    use postcard::PathSegment;
+
+    let err =
+        from_bytes_detailed::<Packet>(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x34, 0x12, 0x07])
+            .unwrap_err();
+    assert_eq!(
+        err.path(),
+        &[
+            PathSegment::Field("header"),
+            PathSegment::Field("kind"),
+            PathSegment::Variant("Data"),
+            PathSegment::Field("valid"),
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "Found a bool that wasn't 0 or 1 at offset 9 in `header.kind::Data.valid`"
+    );
+
+    // The variant is not known yet when the discriminant is bad
+    let err = from_bytes_detailed::<Packet>(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x05]).unwrap_err();
+    assert_eq!(
+        err.path(),
+        &[PathSegment::Field("header"), PathSegment::Field("kind")]
+    );
+
+    let err = from_bytes_detailed::<Packet>(&[
+        0x01, 0x00, 0x00, 0x00, 0x02, 0x02, 0x00, 0x01, 0x02, 0x02,
+    ])
+    .unwrap_err();
+    assert_eq!(err.kind(), &Error::DeserializeBadOption);
+    assert_eq!(
+        err.to_string(),
+        "Found an Option discriminant that wasn't 0 or 1 at offset 10 in `header.kind::Many[2]`"
+    );
+
+    // Completed values are not part of the path
+    let err =
+        from_bytes_detailed::<Packet>(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0xCD]).unwrap_err();
+    assert_eq!(err.path(), &[PathSegment::Field("crc")]);
+
+    let err = from_bytes_detailed::<Vec<(u8, bool)>>(&[0x02, 0x01, 0x00, 0x02, 0x03]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Found a bool that wasn't 0 or 1 at offset 5 in `[1][1]`"
+    );
+
+    let err = from_bytes_detailed::<u8>(&[]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Hit the end of buffer, expected more data at offset 0"
+    );
+}
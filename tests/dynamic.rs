@@ -0,0 +1,193 @@
+#![cfg(all(feature = "alloc", feature = "derive"))]
+
+use std::collections::BTreeMap;
+
+use postcard::{
+    dynamic::{self, Value},
+    schema::OwnedNamedType,
+    Deserializer, Error, Schema, Serializer,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Schema, Debug, PartialEq)]
+struct Telemetry {
+    seq: u32,
+    temp: f32,
+    name: String,
+    status: Status,
+    readings: Vec<Reading>,
+    tags: BTreeMap<u8, String>,
+}
+
+#[derive(Serialize, Deserialize, Schema, Debug, PartialEq)]
+struct Reading(i16, Option<char>);
+
+#[derive(Serialize, Deserialize, Schema, Debug, PartialEq)]
+enum Status {
+    Idle,
+    Error(u16),
+    Moving(i32, i32),
+    Charging { level: u8, full: bool },
+}
+
+fn owned_schema<T: Schema>() -> OwnedNamedType {
+    T::SCHEMA.into()
+}
+
+fn data(status: Status) -> Telemetry {
+    let mut tags = BTreeMap::new();
+    tags.insert(1, String::from("one"));
+    tags.insert(200, String::from("two hundred"));
+
+    Telemetry {
+        seq: 0x1234_5678,
+        temp: 21.5,
+        name: String::from("probe"),
+        status,
+        readings: vec![Reading(-3, Some('x')), Reading(i16::MAX, None)],
+        tags,
+    }
+}
+
+#[test]
+fn decode_telemetry() {
+    let bytes = postcard::to_allocvec(&data(Status::Charging {
+        level: 80,
+        full: false,
+    }))
+    .unwrap();
+    let value = dynamic::from_bytes(&owned_schema::<Telemetry>(), &bytes).unwrap();
+
+    let expected = Value::Struct(vec![
+        ("seq".into(), Value::Unsigned(0x1234_5678)),
+        ("temp".into(), Value::F32(21.5)),
+        ("name".into(), Value::String("probe".into())),
+        (
+            "status".into(),
+            Value::Variant {
+                name: "Charging".into(),
+                value: Box::new(Value::Struct(vec![
+                    ("level".into(), Value::Unsigned(80)),
+                    ("full".into(), Value::Bool(false)),
+                ])),
+            },
+        ),
+        (
+            "readings".into(),
+            Value::Seq(vec![
+                Value::Seq(vec![
+                    Value::Signed(-3),
+                    Value::Option(Some(Box::new(Value::Char('x')))),
+                ]),
+                Value::Seq(vec![Value::Signed(i16::MAX.into()), Value::Option(None)]),
+            ]),
+        ),
+        (
+            "tags".into(),
+            Value::Map(vec![
+                (Value::Unsigned(1), Value::String("one".into())),
+                (Value::Unsigned(200), Value::String("two hundred".into())),
+            ]),
+        ),
+    ]);
+    assert_eq!(value, expected);
+
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"{"seq":305419896,"temp":21.5,"name":"probe","status":{"Charging":{"level":80,"full":false}},"readings":[[-3,"x"],[32767,null]],"tags":{"1":"one","200":"two hundred"}}"#
+    );
+}
+
+#[test]
+fn roundtrip_variants() {
+    let schema = owned_schema::<Telemetry>();
+    for status in [
+        Status::Idle,
+        Status::Error(500),
+        Status::Moving(-1, 1),
+        Status::Charging {
+            level: 1,
+            full: true,
+        },
+    ] {
+        let bytes = postcard::to_allocvec(&data(status)).unwrap();
+        let (value, rest) = dynamic::take_from_bytes(&schema, &bytes).unwrap();
+        assert!(rest.is_empty());
+
+        assert_eq!(dynamic::to_allocvec(&schema, &value).unwrap(), bytes);
+
+        let mut buf = [0u8; 128];
+        assert_eq!(
+            dynamic::to_slice(&schema, &value, &mut buf).unwrap(),
+            &bytes[..]
+        );
+    }
+}
+
+#[test]
+fn varint_encoding() {
+    let schema = owned_schema::<Telemetry>();
+    let mut buf = [0u8; 128];
+    let bytes = postcard::to_slice_varint(&data(Status::Moving(-70000, 3)), &mut buf).unwrap();
+
+    let mut deserializer = Deserializer::from_bytes_varint(bytes);
+    let value = dynamic::deserialize(&schema, &mut deserializer).unwrap();
+    assert!(deserializer.finalize().unwrap().is_empty());
+
+    let mut serializer = Serializer::with_int_encoding(
+        postcard::flavors::AllocVec(Vec::new()),
+        postcard::IntEncoding::Varint,
+    );
+    dynamic::serialize(&schema, &value, &mut serializer).unwrap();
+    assert_eq!(serializer.output.0, bytes);
+
+    let out: Telemetry = postcard::from_bytes_varint(&serializer.output.0).unwrap();
+    assert_eq!(out, data(Status::Moving(-70000, 3)));
+}
+
+#[test]
+fn mismatches() {
+    let schema = owned_schema::<Reading>();
+
+    let bytes = postcard::to_allocvec(&Reading(1, None)).unwrap();
+    assert_eq!(
+        dynamic::from_bytes(&schema, &bytes[..1]),
+        Err(Error::DeserializeUnexpectedEnd)
+    );
+    assert_eq!(
+        dynamic::from_bytes(&schema, &[0x00, 0x00, 0x02]),
+        Err(Error::DeserializeBadOption)
+    );
+    assert_eq!(
+        dynamic::from_bytes(&owned_schema::<Status>(), &[0x04]),
+        Err(Error::DeserializeBadEnum)
+    );
+
+    // Out of range for an `i16`
+    let value = Value::Seq(vec![Value::Signed(40000), Value::Option(None)]);
+    match dynamic::to_allocvec(&schema, &value) {
+        Err(Error::SerdeSerCustom(msg)) => {
+            assert!(msg
+                .as_str()
+                .starts_with("value Signed(40000) does not match schema"))
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // Wrong number of fields
+    let value = Value::Seq(vec![Value::Signed(1)]);
+    assert!(matches!(
+        dynamic::to_allocvec(&schema, &value),
+        Err(Error::SerdeSerCustom(_))
+    ));
+
+    // Unknown variant
+    let value = Value::Variant {
+        name: "Sleeping".into(),
+        value: Box::new(Value::Unit),
+    };
+    assert!(matches!(
+        dynamic::to_allocvec(&owned_schema::<Status>(), &value),
+        Err(Error::SerdeSerCustom(_))
+    ));
+}
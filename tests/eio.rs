@@ -0,0 +1,116 @@
+#![cfg(feature = "embedded-io")]
+
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+use postcard::{from_bytes, from_eio, to_eio, to_slice, Error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Reading<'a> {
+    id: u32,
+    unit: &'a str,
+    samples: [i16; 4],
+}
+
+const READING: Reading<'static> = Reading {
+    id: 0xDEAD_BEEF,
+    unit: "mV",
+    samples: [-1, 0, 1, 0x7FFF],
+};
+
+/// A UART, which transfers a single byte at a time, and fails once `limit` bytes have been
+/// transferred
+struct Uart {
+    tx: Vec<u8>,
+    rx: Vec<u8>,
+    limit: usize,
+    flushes: usize,
+}
+
+impl Uart {
+    fn new(rx: &[u8], limit: usize) -> Self {
+        Uart {
+            tx: Vec::new(),
+            rx: rx.to_vec(),
+            limit,
+            flushes: 0,
+        }
+    }
+}
+
+impl ErrorType for Uart {
+    type Error = ErrorKind;
+}
+
+impl Write for Uart {
+    fn write(&mut self, data: &[u8]) -> Result<usize, ErrorKind> {
+        if self.tx.len() >= self.limit {
+            return Err(ErrorKind::Other);
+        }
+        self.tx.push(data[0]);
+        Ok(1)
+    }
+
+    fn flush(&mut self) -> Result<(), ErrorKind> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+impl Read for Uart {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+        if self.limit == 0 {
+            return Err(ErrorKind::Other);
+        }
+        if self.rx.is_empty() {
+            return Ok(0);
+        }
+        self.limit -= 1;
+        buf[0] = self.rx.remove(0);
+        Ok(1)
+    }
+}
+
+#[test]
+fn write() {
+    let mut buf = [0u8; 32];
+    let expected = to_slice(&READING, &mut buf).unwrap();
+
+    let uart = to_eio(&READING, Uart::new(&[], 64)).unwrap();
+    assert_eq!(uart.tx, expected);
+    assert_eq!(uart.flushes, 1);
+    assert_eq!(from_bytes::<Reading>(&uart.tx).unwrap(), READING);
+
+    let mut uart = Uart::new(&[], expected.len() - 1);
+    assert_eq!(
+        to_eio(&READING, &mut uart).err(),
+        Some(Error::SerializeIoError)
+    );
+    assert_eq!(uart.tx, &expected[..expected.len() - 1]);
+    assert_eq!(uart.flushes, 0);
+}
+
+#[test]
+fn read() {
+    let mut buf = [0u8; 32];
+    let used = to_slice(&READING, &mut buf).unwrap().len();
+    buf[used] = 0xAA;
+    let stream = &buf[..used + 1];
+
+    let mut scratch = [0u8; 2];
+    let (out, uart) = from_eio::<Reading, _>(Uart::new(stream, 64), &mut scratch).unwrap();
+    assert_eq!(out, READING);
+    assert_eq!(uart.rx, &[0xAA]);
+
+    assert_eq!(
+        from_eio::<Reading, _>(Uart::new(stream, 64), &mut [0u8; 1]).err(),
+        Some(Error::DeserializeScratchTooSmall)
+    );
+    assert_eq!(
+        from_eio::<Reading, _>(Uart::new(&stream[..used - 1], 64), &mut [0u8; 2]).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+    assert_eq!(
+        from_eio::<Reading, _>(Uart::new(stream, 4), &mut [0u8; 2]).err(),
+        Some(Error::DeserializeIoError)
+    );
+}
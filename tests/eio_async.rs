@@ -0,0 +1,129 @@
+#![cfg(feature = "embedded-io-async")]
+
+use embassy_futures::{block_on, join::join};
+use embassy_sync::{blocking_mutex::raw::NoopRawMutex, pipe::Pipe};
+use embedded_io_async::{BufRead, ErrorKind, ErrorType};
+use postcard::{
+    from_async_reader, to_async_writer, to_async_writer_cobs, to_slice, CobsAccumulator, Error,
+    StatsMonitor,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+enum Command {
+    Ping(u32),
+    Move { x: i16, y: i16 },
+    Stop,
+}
+
+const COMMANDS: [Command; 4] = [
+    Command::Ping(0xDEAD_BEEF),
+    Command::Move { x: -300, y: 0 },
+    Command::Stop,
+    Command::Ping(0),
+];
+
+#[test]
+fn loopback() {
+    // The pipe is smaller than a message, so both sides have to wait for each other
+    let mut pipe = Pipe::<NoopRawMutex, 4>::new();
+    let (mut rx, mut tx) = pipe.split();
+
+    let send = async {
+        let mut buf = [0u8; 16];
+        for cmd in COMMANDS.iter() {
+            to_async_writer(cmd, &mut tx, &mut buf).await.unwrap();
+        }
+    };
+    let recv = async {
+        let mut buf = [0u8; 16];
+        for cmd in COMMANDS.iter() {
+            let (out, _) = from_async_reader::<Command, _>(&mut rx, &mut buf)
+                .await
+                .unwrap();
+            assert_eq!(&out, cmd);
+        }
+    };
+    block_on(join(send, recv));
+}
+
+#[test]
+fn loopback_cobs() {
+    let mut pipe = Pipe::<NoopRawMutex, 4>::new();
+    let (mut rx, mut tx) = pipe.split();
+
+    let send = async {
+        let mut buf = [0u8; 16];
+        for cmd in COMMANDS.iter() {
+            to_async_writer_cobs(cmd, &mut tx, &mut buf).await.unwrap();
+        }
+    };
+    let recv = async {
+        let mut acc: CobsAccumulator<16, StatsMonitor> = CobsAccumulator::default();
+        for cmd in COMMANDS.iter() {
+            assert_eq!(
+                acc.read_frame::<Command, _>(&mut rx).await.as_ref(),
+                Ok(cmd)
+            );
+        }
+        assert_eq!(acc.monitor().stats().frames_ok, 4);
+    };
+    block_on(join(send, recv));
+}
+
+/// A reader which fails after returning its data
+struct Broken<'a>(&'a [u8]);
+
+impl ErrorType for Broken<'_> {
+    type Error = ErrorKind;
+}
+
+impl BufRead for Broken<'_> {
+    async fn fill_buf(&mut self) -> Result<&[u8], ErrorKind> {
+        if self.0.is_empty() {
+            return Err(ErrorKind::Other);
+        }
+        Ok(self.0)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0 = &self.0[amt..];
+    }
+}
+
+#[test]
+fn async_errors() {
+    let mut out = [0u8; 8];
+    assert_eq!(
+        block_on(to_async_writer(&COMMANDS[0], &mut out[..], &mut [0u8; 4])).err(),
+        Some(Error::SerializeBufferFull)
+    );
+    assert_eq!(
+        block_on(to_async_writer(&COMMANDS[0], &mut out[..4], &mut [0u8; 8])).err(),
+        Some(Error::SerializeIoError)
+    );
+
+    let data = to_slice(&COMMANDS[0], &mut out).unwrap();
+    assert_eq!(
+        block_on(from_async_reader::<Command, _>(&*data, &mut [0u8; 4])).err(),
+        Some(Error::DeserializeBufferFull)
+    );
+    assert_eq!(
+        block_on(from_async_reader::<Command, _>(&data[..4], &mut [0u8; 8])).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+    assert_eq!(
+        block_on(from_async_reader::<Command, _>(
+            Broken(&data[..4]),
+            &mut [0u8; 8]
+        ))
+        .err(),
+        Some(Error::DeserializeIoError)
+    );
+
+    let mut acc: CobsAccumulator<8> = CobsAccumulator::new();
+    assert_eq!(
+        block_on(acc.read_frame::<Command, _>(&mut Broken(&[0x02, 0x01]))),
+        Err(Error::DeserializeIoError)
+    );
+}
@@ -0,0 +1,117 @@
+use postcard::{to_slice_cobs, CobsFrames, Error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Message<'a> {
+    id: u8,
+    name: &'a str,
+}
+
+fn stream(garbage: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x00];
+    for id in 0..3 {
+        let mut buf = [0u8; 32];
+        let msg = Message { id, name: "frame" };
+        out.extend_from_slice(to_slice_cobs(&msg, &mut buf).unwrap());
+        if id == 1 {
+            out.extend_from_slice(garbage);
+        }
+    }
+    out
+}
+
+#[test]
+fn frames() {
+    // Not valid COBS, as the frame ends in the middle of a block
+    let mut buf = stream(&[0x05, 0x01, 0x02, 0x00]);
+    buf.extend_from_slice(&[0x03, 0x01]);
+
+    let mut frames = CobsFrames::<Message>::new(&mut buf);
+    let ids: Vec<_> = frames
+        .by_ref()
+        .map(|res| res.map(|msg| (msg.id, msg.name)))
+        .collect();
+    assert_eq!(
+        ids,
+        &[
+            Ok((0, "frame")),
+            Ok((1, "frame")),
+            Err(Error::DeserializeBadEncoding),
+            Ok((2, "frame")),
+        ]
+    );
+    assert_eq!(frames.discarded(), 4);
+    assert_eq!(frames.remainder(), &[0x03, 0x01]);
+}
+
+#[cfg(feature = "use-std")]
+mod stream {
+    use super::*;
+    use postcard::CobsStream;
+    use std::io::{self, Read};
+
+    /// Reads at most three bytes at a time, and fails at the end of the input
+    struct Trickle<'a> {
+        data: &'a [u8],
+        fail: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() && self.fail {
+                return Err(io::Error::other("gone"));
+            }
+            let len = buf.len().min(3);
+            self.data.read(&mut buf[..len])
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq, Eq)]
+    struct Owned {
+        id: u8,
+        name: String,
+    }
+
+    #[test]
+    fn cobs_stream() {
+        // Too large for the accumulator
+        let mut garbage = vec![0x10; 20];
+        garbage.push(0x00);
+        let data = stream(&garbage);
+        let reader = Trickle {
+            data: &data,
+            fail: false,
+        };
+
+        let stream = CobsStream::<_, Owned, 12>::new(reader);
+        let ids: Vec<_> = stream.map(|res| res.map(|msg| msg.id)).collect();
+        assert_eq!(
+            ids,
+            &[Ok(0), Ok(1), Err(Error::DeserializeBufferFull), Ok(2)]
+        );
+    }
+
+    #[test]
+    fn io_error() {
+        let data = stream(&[0x02, 0x01, 0x00]);
+        let reader = Trickle {
+            data: &data,
+            fail: true,
+        };
+
+        let mut stream = CobsStream::<_, Owned, 12>::new(reader);
+        let ids: Vec<_> = stream.by_ref().map(|res| res.map(|msg| msg.id)).collect();
+        assert_eq!(
+            ids,
+            &[
+                Ok(0),
+                Ok(1),
+                Err(Error::DeserializeUnexpectedEnd),
+                Ok(2),
+                Err(Error::DeserializeIoError),
+            ]
+        );
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.discarded(), 3);
+    }
+}
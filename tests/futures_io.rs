@@ -0,0 +1,148 @@
+#![cfg(feature = "futures-io")]
+
+use futures::{channel::mpsc, executor::block_on, future::join, io::AsyncWrite, TryStreamExt};
+use postcard::{
+    from_futures_reader, to_futures_writer, to_futures_writer_cobs, CobsAccumulator, Error,
+};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+enum Command {
+    Ping(u32),
+    Move { x: i16, y: i16 },
+    Stop,
+}
+
+const COMMANDS: &[Command] = &[
+    Command::Ping(0xDEAD_BEEF),
+    Command::Move { x: -300, y: 0 },
+    Command::Stop,
+    Command::Ping(0),
+];
+
+/// The sending half of a pipe, which passes on at most three bytes per write, so that
+/// messages arrive in pieces
+struct PipeWriter(mpsc::Sender<io::Result<Vec<u8>>>);
+
+impl AsyncWrite for PipeWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.0.poll_ready(cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(_)) => return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+            Poll::Pending => return Poll::Pending,
+        }
+        let len = buf.len().min(3);
+        match self.0.start_send(Ok(buf[..len].to_vec())) {
+            Ok(()) => Poll::Ready(Ok(len)),
+            Err(_) => Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.0.close_channel();
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn loopback() {
+    let (tx, rx) = mpsc::channel(0);
+    let mut tx = PipeWriter(tx);
+    let mut rx = rx.into_async_read();
+
+    let send = async {
+        let mut buf = [0u8; 16];
+        for cmd in COMMANDS.iter() {
+            tx = to_futures_writer(cmd, tx, &mut buf).await.unwrap();
+        }
+    };
+    let recv = async {
+        let mut buf = [0u8; 16];
+        for cmd in COMMANDS.iter() {
+            let (out, _) = from_futures_reader::<Command, _>(&mut rx, &mut buf)
+                .await
+                .unwrap();
+            assert_eq!(&out, cmd);
+        }
+        assert_eq!(
+            from_futures_reader::<Command, _>(&mut rx, &mut buf)
+                .await
+                .err(),
+            Some(Error::DeserializeUnexpectedEnd)
+        );
+    };
+    block_on(join(send, recv));
+}
+
+#[test]
+fn loopback_cobs() {
+    let (tx, rx) = mpsc::channel(0);
+    let mut tx = PipeWriter(tx);
+    let mut rx = rx.into_async_read();
+
+    let send = async {
+        let mut buf = [0u8; 16];
+        for cmd in COMMANDS.iter() {
+            tx = to_futures_writer_cobs(cmd, tx, &mut buf).await.unwrap();
+        }
+    };
+    let recv = async {
+        let mut acc: CobsAccumulator<16> = CobsAccumulator::new();
+        for cmd in COMMANDS.iter() {
+            assert_eq!(
+                acc.read_futures_frame::<Command, _>(&mut rx).await.as_ref(),
+                Ok(cmd)
+            );
+        }
+    };
+    block_on(join(send, recv));
+}
+
+#[test]
+fn futures_errors() {
+    assert_eq!(
+        block_on(to_futures_writer(&COMMANDS[0], Vec::new(), &mut [0u8; 4])).err(),
+        Some(Error::SerializeBufferFull)
+    );
+
+    let (tx, rx) = mpsc::channel(0);
+    drop(rx);
+    assert_eq!(
+        block_on(to_futures_writer(
+            &COMMANDS[0],
+            PipeWriter(tx),
+            &mut [0u8; 8]
+        ))
+        .err(),
+        Some(Error::SerializeIoError)
+    );
+
+    let data = postcard::to_stdvec(&COMMANDS[0]).unwrap();
+    assert_eq!(
+        block_on(from_futures_reader::<Command, _>(&*data, &mut [0u8; 4])).err(),
+        Some(Error::DeserializeBufferFull)
+    );
+
+    let (mut tx, rx) = mpsc::unbounded::<io::Result<Vec<u8>>>();
+    tx.start_send(Ok(data[..2].to_vec())).unwrap();
+    tx.start_send(Err(io::ErrorKind::Other.into())).unwrap();
+    assert_eq!(
+        block_on(from_futures_reader::<Command, _>(
+            rx.into_async_read(),
+            &mut [0u8; 8]
+        ))
+        .err(),
+        Some(Error::DeserializeIoError)
+    );
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "heapless")]
+
+use heapless::Vec as HVec;
+use postcard::{
+    flavors::{Hdlc, Slice},
+    from_bytes_hdlc, serialize_with_flavor, take_from_bytes_hdlc, to_slice_hdlc, Error, FeedResult,
+    HdlcAccumulator,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Message<'a> {
+    id: u16,
+    body: &'a [u8],
+}
+
+const MSG: Message<'static> = Message {
+    id: 0x7E7D,
+    body: &[0x00, 0x7E, 0x7D, 0x5E, 0x5D, 0x20],
+};
+
+#[test]
+fn escaping() {
+    let mut buf = [0u8; 32];
+    let used = to_slice_hdlc(&MSG, &mut buf).unwrap();
+    assert_eq!(
+        &used[..13],
+        &[0x7E, 0x7D, 0x5D, 0x7D, 0x5E, 0x06, 0x00, 0x7D, 0x5E, 0x7D, 0x5D, 0x5E, 0x5D]
+    );
+    assert_eq!(used.iter().filter(|b| **b == 0x7E).count(), 2);
+    assert_eq!(used.last(), Some(&0x7E));
+
+    let out: Message = from_bytes_hdlc(used).unwrap();
+    assert_eq!(out, MSG);
+}
+
+#[test]
+fn escaped_fcs() {
+    // The frame check sequence of these bytes contains a flag byte
+    let mut buf = [0u8; 16];
+    let used = to_slice_hdlc(&[0x1Bu8, 0x00], &mut buf).unwrap();
+    assert_eq!(used, &[0x7E, 0x1B, 0x00, 0x7D, 0x5E, 0x7D, 0x5E, 0x7E]);
+    assert_eq!(from_bytes_hdlc::<[u8; 2]>(used).unwrap(), [0x1B, 0x00]);
+}
+
+#[test]
+fn roundtrip_framing() {
+    let mut buf = [0u8; 64];
+    let used = serialize_with_flavor::<_, Hdlc<Slice>, &mut [u8]>(
+        &(MSG.id, 1234u32),
+        Hdlc::try_new(Slice::new(&mut buf)).unwrap(),
+    )
+    .unwrap();
+
+    // Two frames sharing a single flag byte between them
+    let mut stream = [0u8; 64];
+    let len = used.len();
+    stream[..len].copy_from_slice(used);
+    stream[len..][..len - 1].copy_from_slice(&used[1..]);
+    let stream = &mut stream[..2 * len - 1];
+
+    let (out, rest) = take_from_bytes_hdlc::<(u16, u32)>(stream).unwrap();
+    assert_eq!(out, (MSG.id, 1234));
+    assert_eq!(rest.len(), len - 1);
+    let (out, rest) = take_from_bytes_hdlc::<(u16, u32)>(rest).unwrap();
+    assert_eq!(out, (MSG.id, 1234));
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn bad_frames() {
+    let mut buf = [0u8; 16];
+    let used = to_slice_hdlc(&0x1234u16, &mut buf).unwrap();
+    used[1] ^= 0x01;
+    assert_eq!(
+        from_bytes_hdlc::<u16>(used).err(),
+        Some(Error::DeserializeBadCrc)
+    );
+
+    // Abort sequence
+    assert_eq!(
+        from_bytes_hdlc::<u8>(&mut [0x7E, 0x01, 0x7D, 0x7E]).err(),
+        Some(Error::DeserializeBadEncoding)
+    );
+    assert_eq!(
+        from_bytes_hdlc::<u8>(&mut [0x7E, 0x01, 0x7E]).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+}
+
+#[test]
+fn buffer_full() {
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        to_slice_hdlc(&[0x00u8; 2], &mut buf).err(),
+        Some(Error::SerializeBufferFull)
+    );
+    assert_eq!(
+        to_slice_hdlc(&(), &mut []).err(),
+        Some(Error::SerializeBufferFull)
+    );
+}
+
+#[test]
+fn accumulator() {
+    let mut buf = [0u8; 32];
+    let frame = to_slice_hdlc(&MSG.body, &mut buf).unwrap();
+
+    // Feed byte by byte
+    let mut acc: HdlcAccumulator<16> = HdlcAccumulator::new();
+    let (last, head) = frame.split_last().unwrap();
+    for byte in head {
+        assert!(matches!(
+            acc.feed::<HVec<u8, 16>>(core::slice::from_ref(byte)),
+            FeedResult::Consumed
+        ));
+    }
+    match acc.feed::<HVec<u8, 16>>(core::slice::from_ref(last)) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(&data[..], MSG.body);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+
+    // Several frames in a single chunk, the second with a bad frame check sequence
+    let mut corrupt = [0u8; 32];
+    corrupt[..frame.len()].copy_from_slice(frame);
+    corrupt[3] ^= 0x01;
+    let mut stream = Vec::new();
+    stream.extend_from_slice(frame);
+    stream.extend_from_slice(&corrupt[..frame.len()]);
+    stream.extend_from_slice(frame);
+    let remaining = match acc.feed::<HVec<u8, 16>>(&stream) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(&data[..], MSG.body);
+            remaining
+        }
+        _ => panic!(),
+    };
+    let remaining = match acc.feed::<HVec<u8, 16>>(remaining) {
+        FeedResult::DeserError(remaining) => remaining,
+        _ => panic!(),
+    };
+    match acc.feed::<HVec<u8, 16>>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(&data[..], MSG.body);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn accumulator_overfull() {
+    let mut buf = [0u8; 64];
+    let big = to_slice_hdlc(&[0x7Eu8; 20], &mut buf).unwrap();
+    let mut small_buf = [0u8; 8];
+    let small = to_slice_hdlc(&7u8, &mut small_buf).unwrap();
+
+    let mut acc: HdlcAccumulator<8> = HdlcAccumulator::new();
+    let (first, second) = big.split_at(big.len() / 2);
+    assert!(matches!(acc.feed::<u8>(first), FeedResult::Consumed));
+
+    let mut stream = Vec::new();
+    stream.extend_from_slice(second);
+    stream.extend_from_slice(small);
+    let remaining = match acc.feed::<u8>(&stream) {
+        FeedResult::OverFull(remaining) => remaining,
+        _ => panic!(),
+    };
+    assert_eq!(remaining, small);
+    match acc.feed::<u8>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, 7);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
@@ -0,0 +1,210 @@
+#![cfg(feature = "use-std")]
+
+use std::io::{self, Write};
+
+use postcard::{from_bytes, from_io, to_io, to_stdvec, Error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Record {
+    id: u32,
+    name: String,
+    data: Vec<u8>,
+}
+
+fn data() -> Record {
+    Record {
+        id: 0xDEAD_BEEF,
+        name: String::from("streaming postcard records"),
+        data: vec![0x00, 0x01, 0xFF],
+    }
+}
+
+/// A writer which accepts a limited number of bytes
+struct Limited {
+    buf: Vec<u8>,
+    limit: usize,
+    flushes: usize,
+}
+
+impl Write for Limited {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let remaining = self.limit - self.buf.len();
+        if remaining == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+        }
+        let ct = remaining.min(data.len());
+        self.buf.extend_from_slice(&data[..ct]);
+        Ok(ct)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+struct FailFlush;
+
+impl Write for FailFlush {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::other("flush"))
+    }
+}
+
+#[test]
+fn io_matches_stdvec() {
+    let out = to_io(&data(), Vec::new()).unwrap();
+    assert_eq!(out, to_stdvec(&data()).unwrap());
+    assert_eq!(from_bytes::<Record>(&out).unwrap(), data());
+}
+
+/// A writer which records the length of every write
+struct Counting {
+    buf: Vec<u8>,
+    writes: Vec<usize>,
+}
+
+impl Write for Counting {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        self.writes.push(data.len());
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn io_buffered() {
+    let mut writer = Counting {
+        buf: Vec::new(),
+        writes: Vec::new(),
+    };
+    to_io(&data(), &mut writer).unwrap();
+    assert_eq!(writer.buf, to_stdvec(&data()).unwrap());
+    assert_eq!(writer.writes, &[writer.buf.len()]);
+
+    // Bytes are passed on in chunks of 64 bytes, or directly if they do not fit in a chunk
+    let record = Record {
+        id: 1,
+        name: "a".repeat(100),
+        data: vec![0xAA; 70],
+    };
+    let mut writer = Counting {
+        buf: Vec::new(),
+        writes: Vec::new(),
+    };
+    to_io(&record, &mut writer).unwrap();
+    assert_eq!(writer.buf, to_stdvec(&record).unwrap());
+    assert_eq!(writer.writes, &[5, 100, 64, 7]);
+}
+
+#[test]
+fn io_errors() {
+    let expected = to_stdvec(&data()).unwrap();
+
+    let mut writer = Limited {
+        buf: Vec::new(),
+        limit: expected.len(),
+        flushes: 0,
+    };
+    to_io(&data(), &mut writer).unwrap();
+    assert_eq!(writer.buf, expected);
+    assert_eq!(writer.flushes, 1);
+
+    let mut writer = Limited {
+        buf: Vec::new(),
+        limit: expected.len() - 1,
+        flushes: 0,
+    };
+    assert_eq!(
+        to_io(&data(), &mut writer).err(),
+        Some(Error::SerializeIoError)
+    );
+    assert_eq!(writer.buf, &expected[..expected.len() - 1]);
+    assert_eq!(writer.flushes, 0);
+
+    assert_eq!(
+        to_io(&data(), FailFlush).err(),
+        Some(Error::SerializeIoError)
+    );
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Borrowed<'a> {
+    id: u16,
+    name: &'a str,
+    data: &'a [u8],
+}
+
+#[test]
+fn from_io_owned() {
+    let mut stream = to_stdvec(&data()).unwrap();
+    stream.extend_from_slice(&to_stdvec(&data()).unwrap());
+    stream.push(0xAA);
+
+    // Owned data only uses the scratch buffer temporarily
+    let mut scratch = [0u8; 26];
+    let mut reader = stream.as_slice();
+    for _ in 0..2 {
+        let (out, rest) = from_io::<Record, _>(reader, &mut scratch).unwrap();
+        assert_eq!(out, data());
+        reader = rest;
+    }
+    assert_eq!(reader, &[0xAA]);
+
+    let mut scratch = [0u8; 25];
+    assert_eq!(
+        from_io::<Record, _>(stream.as_slice(), &mut scratch).err(),
+        Some(Error::DeserializeScratchTooSmall)
+    );
+}
+
+#[test]
+fn from_io_borrowed() {
+    let msg = Borrowed {
+        id: 0x1234,
+        name: "hello",
+        data: &[0x00, 0x01, 0x02],
+    };
+    let stream = to_stdvec(&msg).unwrap();
+
+    let mut scratch = [0u8; 8];
+    let (out, rest) = from_io::<Borrowed, _>(stream.as_slice(), &mut scratch).unwrap();
+    assert_eq!(out, msg);
+    assert!(rest.is_empty());
+
+    let mut scratch = [0u8; 7];
+    assert_eq!(
+        from_io::<Borrowed, _>(stream.as_slice(), &mut scratch).err(),
+        Some(Error::DeserializeScratchTooSmall)
+    );
+}
+
+#[test]
+fn from_io_errors() {
+    struct Broken;
+
+    impl io::Read for Broken {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken"))
+        }
+    }
+
+    let stream = to_stdvec(&data()).unwrap();
+    assert_eq!(
+        from_io::<Record, _>(&stream[..stream.len() - 1], &mut [0u8; 32]).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+    assert_eq!(
+        from_io::<Record, _>(Broken, &mut [0u8; 32]).err(),
+        Some(Error::DeserializeIoError)
+    );
+}
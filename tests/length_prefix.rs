@@ -0,0 +1,125 @@
+use postcard::{
+    flavors::{LengthPrefix, PrefixFormat, Slice},
+    from_bytes_length_prefix, serialize_with_flavor, take_from_bytes_length_prefix,
+    to_slice_length_prefix, Error, FeedResult, LengthPrefixAccumulator,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Message<'a> {
+    id: u16,
+    body: &'a [u8],
+}
+
+const FORMATS: [PrefixFormat; 3] = [PrefixFormat::Varint, PrefixFormat::U16, PrefixFormat::U32];
+
+#[test]
+fn prefix_formats() {
+    let msg = Message {
+        id: 0x0102,
+        body: &[0xAA; 200],
+    };
+    let mut plain = [0u8; 256];
+    let plain = postcard::to_slice(&msg, &mut plain).unwrap();
+    assert_eq!(plain.len(), 204);
+
+    let mut buf = [0u8; 256];
+    let used = to_slice_length_prefix(&msg, &mut buf, PrefixFormat::Varint).unwrap();
+    assert_eq!(&used[..5], &[0xCC, 0x81, 0x80, 0x80, 0x00]);
+    assert_eq!(&used[5..], plain);
+
+    let used = to_slice_length_prefix(&msg, &mut buf, PrefixFormat::U16).unwrap();
+    assert_eq!(&used[..2], &[0xCC, 0x00]);
+    assert_eq!(&used[2..], plain);
+
+    let used = to_slice_length_prefix(&msg, &mut buf, PrefixFormat::U32).unwrap();
+    assert_eq!(&used[..4], &[0xCC, 0x00, 0x00, 0x00]);
+    assert_eq!(&used[4..], plain);
+
+    for format in FORMATS.iter().copied() {
+        let used = to_slice_length_prefix(&msg, &mut buf, format).unwrap();
+        let out: Message = from_bytes_length_prefix(used, format).unwrap();
+        assert_eq!(out, msg);
+    }
+
+    // A canonical (short) varint prefix is accepted as well
+    let (out, rest) =
+        take_from_bytes_length_prefix::<u16>(&[0x02, 0x34, 0x12, 0xFF], PrefixFormat::Varint)
+            .unwrap();
+    assert_eq!(out, 0x1234);
+    assert_eq!(rest, &[0xFF]);
+}
+
+#[test]
+fn prefix_errors() {
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        to_slice_length_prefix(&0u8, &mut buf[..1], PrefixFormat::U16),
+        Err(Error::SerializeBufferFull)
+    );
+
+    // Too long for a u16 prefix
+    let body = [0u8; 70_000];
+    let mut big = vec![0u8; 70_010];
+    assert_eq!(
+        serialize_with_flavor::<[u8], LengthPrefix<Slice>, &mut [u8]>(
+            &body[..],
+            LengthPrefix::try_new(Slice::new(&mut big), PrefixFormat::U16).unwrap(),
+        ),
+        Err(Error::SerializeBufferFull)
+    );
+    assert!(to_slice_length_prefix(&body[..], &mut big, PrefixFormat::U32).is_ok());
+
+    let used = to_slice_length_prefix(&0x1234u16, &mut buf, PrefixFormat::U16).unwrap();
+    assert_eq!(
+        from_bytes_length_prefix::<u16>(&used[..3], PrefixFormat::U16),
+        Err(Error::DeserializeUnexpectedEnd)
+    );
+    assert_eq!(
+        from_bytes_length_prefix::<u16>(&[0x80; 6], PrefixFormat::Varint),
+        Err(Error::DeserializeBadVarint)
+    );
+}
+
+#[test]
+fn accumulate_stream() {
+    let bodies: [&[u8]; 4] = [&[], &[0x00; 3], &[0x55; 40], &[0xFF; 7]];
+
+    for format in FORMATS.iter().copied() {
+        // Several messages back to back, with one that is too large in between
+        let mut stream = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            let mut buf = [0u8; 64];
+            let msg = Message { id: i as u16, body };
+            stream.extend_from_slice(to_slice_length_prefix(&msg, &mut buf, format).unwrap());
+        }
+
+        for chunk_size in 1..=stream.len() {
+            let mut acc: LengthPrefixAccumulator<32> = LengthPrefixAccumulator::new(format);
+            let mut ids = Vec::new();
+            let mut overfull = 0;
+
+            for chunk in stream.chunks(chunk_size) {
+                let mut window = chunk;
+                while !window.is_empty() {
+                    window = match acc.feed::<(u16, Vec<u8>)>(window) {
+                        FeedResult::Consumed => break,
+                        FeedResult::OverFull(rest) => {
+                            overfull += 1;
+                            rest
+                        }
+                        FeedResult::DeserError(_) => panic!("bad frame"),
+                        FeedResult::Success { data, remaining } => {
+                            assert_eq!(data.1, bodies[data.0 as usize]);
+                            ids.push(data.0);
+                            remaining
+                        }
+                    };
+                }
+            }
+
+            assert_eq!(ids, &[0, 1, 3], "chunk size {}", chunk_size);
+            assert_eq!(overfull, 1, "chunk size {}", chunk_size);
+        }
+    }
+}
@@ -52,6 +52,7 @@ struct NewTypeStruct(u32);
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 struct TupleStruct((u8, u16));
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 struct RefStruct<'a> {
     bytes: &'a [u8],
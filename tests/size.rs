@@ -0,0 +1,99 @@
+use postcard::{
+    flavors::{Cobs, Hdlc, LengthPrefix, PrefixFormat, Size, Slip},
+    serialize_with_flavor, serialized_size, to_slice, to_slice_cobs, to_slice_hdlc,
+    to_slice_length_prefix, to_slice_slip,
+};
+use serde::{Serialize, Serializer};
+
+#[derive(Serialize)]
+struct Message<'a> {
+    id: u32,
+    name: &'a str,
+    body: &'a [u8],
+    kind: Option<Kind>,
+}
+
+#[derive(Serialize)]
+enum Kind {
+    A,
+    B(u64),
+}
+
+/// Serializes a sequence with an unknown length
+struct Unsized<'a>(&'a [u8]);
+
+impl Serialize for Unsized<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().filter(|_| true))
+    }
+}
+
+fn messages() -> [Message<'static>; 3] {
+    [
+        Message {
+            id: 0,
+            name: "",
+            body: &[],
+            kind: None,
+        },
+        Message {
+            id: 0xC0DE_7E00,
+            name: "sensor",
+            body: &[0x00; 200],
+            kind: Some(Kind::A),
+        },
+        Message {
+            id: 1,
+            name: "\u{1F980}",
+            body: &[0xC0, 0xDB, 0x7E, 0x7D],
+            kind: Some(Kind::B(u64::MAX)),
+        },
+    ]
+}
+
+#[test]
+fn matches_slice() {
+    let mut buf = [0u8; 512];
+    for msg in messages().iter() {
+        let used = to_slice(msg, &mut buf).unwrap();
+        assert_eq!(serialized_size(msg).unwrap(), used.len());
+    }
+
+    let used = to_slice(&Unsized(&[1, 2, 3]), &mut buf).unwrap();
+    assert_eq!(serialized_size(&Unsized(&[1, 2, 3])).unwrap(), used.len());
+}
+
+#[test]
+fn framed() {
+    let mut buf = [0u8; 512];
+    for msg in messages().iter() {
+        let used = to_slice_cobs(msg, &mut buf).unwrap();
+        let size = serialize_with_flavor::<_, Cobs<Size>, usize>(
+            msg,
+            Cobs::try_new(Size::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(size, used.len());
+
+        let used = to_slice_slip(msg, &mut buf).unwrap();
+        let size =
+            serialize_with_flavor::<_, Slip<Size>, usize>(msg, Slip::new(Size::default())).unwrap();
+        assert_eq!(size, used.len());
+
+        let used = to_slice_hdlc(msg, &mut buf).unwrap();
+        let size = serialize_with_flavor::<_, Hdlc<Size>, usize>(
+            msg,
+            Hdlc::try_new(Size::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(size, used.len());
+
+        let used = to_slice_length_prefix(msg, &mut buf, PrefixFormat::Varint).unwrap();
+        let size = serialize_with_flavor::<_, LengthPrefix<Size>, usize>(
+            msg,
+            LengthPrefix::try_new(Size::default(), PrefixFormat::Varint).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(size, used.len());
+    }
+}
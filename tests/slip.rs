@@ -0,0 +1,167 @@
+#![cfg(feature = "heapless")]
+
+use heapless::Vec as HVec;
+use postcard::{
+    flavors::{Slice, Slip},
+    from_bytes_slip, serialize_with_flavor, take_from_bytes_slip, to_slice_slip, Error, FeedResult,
+    SlipAccumulator,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Message<'a> {
+    id: u16,
+    body: &'a [u8],
+}
+
+const MSG: Message<'static> = Message {
+    id: 0xC0DB,
+    body: &[0x00, 0xC0, 0xDB, 0xDC, 0xDD, 0xC0],
+};
+
+#[test]
+fn escaping() {
+    let mut buf = [0u8; 32];
+    let used = to_slice_slip(&MSG, &mut buf).unwrap();
+    assert_eq!(
+        used,
+        &[
+            0xDB, 0xDD, 0xDB, 0xDC, 0x06, 0x00, 0xDB, 0xDC, 0xDB, 0xDD, 0xDC, 0xDD, 0xDB, 0xDC,
+            0xC0
+        ]
+    );
+
+    let out: Message = from_bytes_slip(used).unwrap();
+    assert_eq!(out, MSG);
+}
+
+#[test]
+fn roundtrip_framing() {
+    let mut buf = [0u8; 64];
+    let used = serialize_with_flavor::<_, Slip<Slice>, &mut [u8]>(
+        &(MSG.id, 1234u32),
+        Slip::new(Slice::new(&mut buf)),
+    )
+    .unwrap();
+    assert_eq!(*used.last().unwrap(), 0xC0);
+    assert_eq!(used.iter().filter(|b| **b == 0xC0).count(), 1);
+
+    // Two frames, the first one with a leading END
+    let mut stream = [0u8; 64];
+    stream[0] = 0xC0;
+    let len = used.len();
+    stream[1..][..len].copy_from_slice(used);
+    stream[1 + len..][..len].copy_from_slice(used);
+    let stream = &mut stream[..1 + 2 * len];
+
+    let (out, rest) = take_from_bytes_slip::<(u16, u32)>(stream).unwrap();
+    assert_eq!(out, (MSG.id, 1234));
+    assert_eq!(rest.len(), len);
+    let (out, rest) = take_from_bytes_slip::<(u16, u32)>(rest).unwrap();
+    assert_eq!(out, (MSG.id, 1234));
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn bad_encoding() {
+    assert_eq!(
+        from_bytes_slip::<u8>(&mut [0xDB, 0x01, 0xC0]).err(),
+        Some(Error::DeserializeBadEncoding)
+    );
+    assert_eq!(
+        from_bytes_slip::<u8>(&mut [0xDB]).err(),
+        Some(Error::DeserializeBadEncoding)
+    );
+    assert_eq!(
+        from_bytes_slip::<u8>(&mut [0xC0, 0xC0]).err(),
+        Some(Error::DeserializeUnexpectedEnd)
+    );
+}
+
+#[test]
+fn buffer_full() {
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        to_slice_slip(&[0xC0u8; 2], &mut buf).err(),
+        Some(Error::SerializeBufferFull)
+    );
+}
+
+#[test]
+fn accumulator() {
+    let mut buf = [0u8; 32];
+    let frame = to_slice_slip(&MSG.body, &mut buf).unwrap();
+
+    // Feed byte by byte, with an empty frame in front
+    let mut acc: SlipAccumulator<16> = SlipAccumulator::new();
+    assert!(matches!(
+        acc.feed::<HVec<u8, 16>>(&[0xC0]),
+        FeedResult::Consumed
+    ));
+    let (last, head) = frame.split_last().unwrap();
+    for byte in head {
+        assert!(matches!(
+            acc.feed::<HVec<u8, 16>>(core::slice::from_ref(byte)),
+            FeedResult::Consumed
+        ));
+    }
+    match acc.feed::<HVec<u8, 16>>(core::slice::from_ref(last)) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(&data[..], MSG.body);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+
+    // Several frames in a single chunk
+    let mut stream = Vec::new();
+    stream.extend_from_slice(frame);
+    stream.extend_from_slice(&[0xDB, 0x01, 0xC0]);
+    stream.extend_from_slice(frame);
+    let remaining = match acc.feed::<HVec<u8, 16>>(&stream) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(&data[..], MSG.body);
+            remaining
+        }
+        _ => panic!(),
+    };
+    let remaining = match acc.feed::<HVec<u8, 16>>(remaining) {
+        FeedResult::DeserError(remaining) => remaining,
+        _ => panic!(),
+    };
+    match acc.feed::<HVec<u8, 16>>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(&data[..], MSG.body);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn accumulator_overfull() {
+    let mut buf = [0u8; 64];
+    let big = to_slice_slip(&[0xC0u8; 20], &mut buf).unwrap();
+    let mut small_buf = [0u8; 8];
+    let small = to_slice_slip(&7u8, &mut small_buf).unwrap();
+
+    let mut acc: SlipAccumulator<8> = SlipAccumulator::new();
+    let (first, second) = big.split_at(big.len() / 2);
+    assert!(matches!(acc.feed::<u8>(first), FeedResult::Consumed));
+
+    let mut stream = Vec::new();
+    stream.extend_from_slice(second);
+    stream.extend_from_slice(small);
+    let remaining = match acc.feed::<u8>(&stream) {
+        FeedResult::OverFull(remaining) => remaining,
+        _ => panic!(),
+    };
+    assert_eq!(remaining, small);
+    match acc.feed::<u8>(remaining) {
+        FeedResult::Success { data, remaining } => {
+            assert_eq!(data, 7);
+            assert!(remaining.is_empty());
+        }
+        _ => panic!(),
+    }
+}
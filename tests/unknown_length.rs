@@ -0,0 +1,177 @@
+#![cfg(feature = "heapless")]
+
+use heapless::{FnvIndexMap, Vec};
+use postcard::{
+    flavors::{Cobs, Size, Slice},
+    from_bytes, serialize_with_flavor, to_slice, to_slice_hdlc, to_slice_slip, to_vec, Error,
+};
+use serde::{Serialize, Serializer};
+
+/// Serializes the even numbers of a slice, through an iterator with an unknown length
+struct Evens<'a>(&'a [u16]);
+
+impl Serialize for Evens<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().filter(|n| *n % 2 == 0))
+    }
+}
+
+/// Serializes the entries of a slice with an even key as a map with an unknown length
+struct EvenKeys<'a>(&'a [(u8, bool)]);
+
+impl Serialize for EvenKeys<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .filter(|(k, _)| k % 2 == 0)
+                .map(|(k, v)| (k, v)),
+        )
+    }
+}
+
+#[test]
+fn seq_backpatched() {
+    let mut buf = [0u8; 32];
+    let used = to_slice(&Evens(&[1, 2, 3, 4, 0x0106]), &mut buf).unwrap();
+    assert_eq!(
+        used,
+        &[0x83, 0x80, 0x80, 0x80, 0x00, 0x02, 0x00, 0x04, 0x00, 0x06, 0x01]
+    );
+
+    let out: Vec<u16, 8> = from_bytes(used).unwrap();
+    assert_eq!(&out[..], &[2, 4, 0x0106]);
+
+    let used = to_slice(&Evens(&[1, 3]), &mut buf).unwrap();
+    assert_eq!(used, &[0x80, 0x80, 0x80, 0x80, 0x00]);
+    let out: Vec<u16, 8> = from_bytes(used).unwrap();
+    assert!(out.is_empty());
+}
+
+#[test]
+fn map_backpatched() {
+    let data = [(1, true), (2, false), (4, true)];
+    let out: Vec<u8, 32> = to_vec(&EvenKeys(&data)).unwrap();
+    assert_eq!(
+        &out[..],
+        &[0x82, 0x80, 0x80, 0x80, 0x00, 0x02, 0x00, 0x04, 0x01]
+    );
+
+    let map: FnvIndexMap<u8, bool, 4> = from_bytes(&out).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&2), Some(&false));
+    assert_eq!(map.get(&4), Some(&true));
+}
+
+#[test]
+fn nested() {
+    #[derive(Serialize)]
+    struct Outer<'a> {
+        a: Evens<'a>,
+        b: (u8, Evens<'a>),
+    }
+
+    let out: Vec<u8, 32> = to_vec(&Outer {
+        a: Evens(&[2]),
+        b: (7, Evens(&[4, 5])),
+    })
+    .unwrap();
+
+    let (a, b): (Vec<u16, 4>, (u8, Vec<u16, 4>)) = from_bytes(&out).unwrap();
+    assert_eq!(&a[..], &[2]);
+    assert_eq!(b.0, 7);
+    assert_eq!(&b.1[..], &[4]);
+}
+
+#[test]
+fn buffer_full() {
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        to_slice(&Evens(&[1]), &mut buf).err(),
+        Some(Error::SerializeBufferFull)
+    );
+}
+
+#[test]
+fn not_patchable() {
+    let mut buf = [0u8; 32];
+    let res = serialize_with_flavor::<_, Cobs<Slice>, &mut [u8]>(
+        &Evens(&[2, 3, 4]),
+        Cobs::try_new(Slice::new(&mut buf)).unwrap(),
+    );
+
+    #[cfg(not(feature = "alloc"))]
+    assert_eq!(res.err(), Some(Error::SerializeSeqLengthUnknown));
+
+    // The elements are buffered, and the length is written as a regular varint
+    #[cfg(feature = "alloc")]
+    {
+        let used = res.unwrap();
+        let out: Vec<u16, 4> = postcard::from_bytes_cobs(used).unwrap();
+        assert_eq!(&out[..], &[2, 4]);
+        assert_eq!(used.len(), 7);
+    }
+}
+
+#[test]
+fn not_patchable_cobs_size() {
+    let res = serialize_with_flavor::<_, Cobs<Size>, usize>(
+        &Evens(&[2, 3, 4]),
+        Cobs::try_new(Size::default()).unwrap(),
+    );
+
+    #[cfg(not(feature = "alloc"))]
+    assert_eq!(res.err(), Some(Error::SerializeSeqLengthUnknown));
+
+    #[cfg(feature = "alloc")]
+    assert_eq!(res.unwrap(), 7);
+}
+
+#[test]
+fn not_patchable_slip() {
+    let mut buf = [0u8; 32];
+    let res = to_slice_slip(&Evens(&[2, 3, 4]), &mut buf);
+
+    #[cfg(not(feature = "alloc"))]
+    assert_eq!(res.err(), Some(Error::SerializeSeqLengthUnknown));
+
+    #[cfg(feature = "alloc")]
+    {
+        let out: Vec<u16, 4> = postcard::from_bytes_slip(res.unwrap()).unwrap();
+        assert_eq!(&out[..], &[2, 4]);
+    }
+}
+
+#[test]
+fn not_patchable_hdlc() {
+    let mut buf = [0u8; 32];
+    let res = to_slice_hdlc(&Evens(&[2, 3, 4]), &mut buf);
+
+    #[cfg(not(feature = "alloc"))]
+    assert_eq!(res.err(), Some(Error::SerializeSeqLengthUnknown));
+
+    #[cfg(feature = "alloc")]
+    {
+        let out: Vec<u16, 4> = postcard::from_bytes_hdlc(res.unwrap()).unwrap();
+        assert_eq!(&out[..], &[2, 4]);
+    }
+}
+
+#[cfg(feature = "use-crc")]
+#[test]
+fn not_patchable_crc() {
+    use crc::{Crc, CRC_16_IBM_SDLC};
+    const CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_SDLC);
+
+    let mut buf = [0u8; 32];
+    let res = postcard::to_slice_crc(&Evens(&[2, 3, 4]), &mut buf, CRC.digest());
+
+    #[cfg(not(feature = "alloc"))]
+    assert_eq!(res.err(), Some(Error::SerializeSeqLengthUnknown));
+
+    #[cfg(feature = "alloc")]
+    {
+        let out: Vec<u16, 4> = postcard::from_bytes_crc(res.unwrap(), CRC.digest()).unwrap();
+        assert_eq!(&out[..], &[2, 4]);
+    }
+}